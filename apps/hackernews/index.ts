@@ -1,6 +1,6 @@
 #!/usr/bin/env bun
 import * as cheerio from 'cheerio';
-import { type AppItem, appPaths, writeAppData } from 'glance-app';
+import { type AppItem, appPaths, previousItems, writeAppData } from 'glance-app';
 import ky from 'ky';
 import minimist from 'minimist';
 import * as path from 'path';
@@ -241,6 +241,9 @@ async function run(): Promise<AppItem[]> {
     const uniqueItems = new Set([...pastItems, ...topItems]);
 
     const itemCache = Object.fromEntries(data.items.map((item) => [item.info.id, item]));
+    const dismissed = new Set(
+      ((await previousItems()) ?? []).filter((item) => item.dismissed).map((item) => item.id)
+    );
 
     for (let itemId of [...uniqueItems]) {
       const existing = itemCache[itemId];
@@ -249,6 +252,12 @@ async function run(): Promise<AppItem[]> {
         continue;
       }
 
+      if (existing && dismissed.has(itemId.toString())) {
+        // The story was already dismissed, so skip fetching and summarizing it again.
+        stories.push(existing);
+        continue;
+      }
+
       const result = await fetchAndProcessStory(itemId, itemCache[itemId]);
       if (result) {
         stories.push(result);
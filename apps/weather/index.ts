@@ -36,8 +36,16 @@ interface Period {
   };
 }
 
-const LAT = process.env.LAT || '21.96163';
-const LON = process.env.LON || '-159.37478';
+interface Settings {
+  lat?: number;
+  lon?: number;
+}
+
+// Settings configured from the dashboard are passed in this variable for scheduled runs.
+const settings: Settings = JSON.parse(process.env.GLANCE_APP_SETTINGS || '{}');
+
+const LAT = settings.lat ?? (process.env.LAT || '21.96163');
+const LON = settings.lon ?? (process.env.LON || '-159.37478');
 
 const baseData = await ky(
   `https://api.weather.gov/points/${LAT},${LON}`
@@ -54,6 +62,13 @@ const appData: AppData = {
   name: 'Weather Forecast',
   path: __filename,
   ui: {},
+  settings_schema: {
+    type: 'object',
+    properties: {
+      lat: { type: 'number', description: 'Latitude of the forecast location' },
+      lon: { type: 'number', description: 'Longitude of the forecast location' },
+    },
+  },
   schedule: [
     {
       cron: '0 */15 * * * *',
@@ -9,6 +9,14 @@
  * The top-level data for the app
  */
 export interface AppData {
+  /**
+   * The command that runs the app, such as `["bun", "run", "index.ts"]`. When this is not set, the platform runs `path`, using an interpreter configured for its extension if there is one.
+   */
+  command?: string[] | null;
+  /**
+   * A URL to which the platform posts each [Feedback](crate::Feedback) on the app's items, such as when the viewer dismisses an item.
+   */
+  feedback_webhook?: string | null;
   /**
    * An array of data items that the app is publishing
    */
@@ -17,6 +25,11 @@ export interface AppData {
    * The name of the app
    */
   name: string;
+  /**
+   * How the app returns its data when the platform runs it on a schedule. When this is not set,
+   * the platform keeps the app's current setting, which starts out as `file`.
+   */
+  output?: AppOutput;
   /**
    * The path at which this app is installed
    */
@@ -25,11 +38,25 @@ export interface AppData {
    * Request that the platform run the app at the specified schedule, if it does not have its own methods of scheduling updates
    */
   schedule?: AppSchedule[];
+  /**
+   * A JSON Schema describing the settings that the app accepts. Users can set values for these settings from the dashboard, and the app can read them using [App::settings](crate::App::settings).
+   */
+  settings_schema?: {
+    [k: string]: unknown;
+  };
   /**
    * Information only used to render the UI of the app
    */
   ui?: AppUiInfo | null;
+  /**
+   * The directory to run the app in. Defaults to the directory containing `path`.
+   */
+  working_dir?: string | null;
 }
+/**
+ * How an app returns its data when the platform runs it on a schedule
+ */
+export type AppOutput = 'file' | 'stdout';
 /**
  * An item published by the app
  */
@@ -123,13 +150,17 @@ export interface NotificationData {
   title: string;
 }
 /**
- * A schedule on which to run this app. This is not implemented yet.
+ * A schedule on which the platform runs this app.
  */
 export interface AppSchedule {
   /**
    * Arguments to pass to the app
    */
   arguments?: string[];
+  /**
+   * The command to run for this schedule, in place of the app's `command`
+   */
+  command?: string[] | null;
   /**
    * The cron schedule for the app
    */
@@ -138,6 +169,14 @@ export interface AppSchedule {
    * How long to wait, in seconds, for the app to execute before killing it and retrying. Defaults to 5 minutes, or 300 seconds. This uses an int instead of a [Duration] for better interoperability with non-Rust apps.
    */
   timeout?: number | null;
+  /**
+   * The IANA timezone, such as "America/New_York", in which to evaluate the cron schedule. Defaults to UTC.
+   */
+  timezone?: string | null;
+  /**
+   * The directory to run this schedule in, in place of the app's `working_dir`
+   */
+  working_dir?: string | null;
 }
 /**
  * Information only used to render the UI of the app
@@ -2,7 +2,7 @@ export * from './app_data.js';
 
 import { promises as fs } from 'node:fs';
 import * as path from 'node:path';
-import type { AppData } from './app_data.js';
+import type { AppData, AppItemData } from './app_data.js';
 
 import envPaths from 'env-paths';
 
@@ -20,7 +20,67 @@ export function appPaths(appId: string) {
   };
 }
 
+/** An item that the app published before, as the platform currently has it. */
+export interface PreviousItem {
+  id: string;
+  data: AppItemData;
+  state_key?: string | null;
+  persistent: boolean;
+  /** True if the viewer has dismissed the item. */
+  dismissed: boolean;
+  updated: string;
+}
+
+/** The items that the app published on earlier runs. These are only available when the platform
+ * runs the app on a schedule, and this returns `null` otherwise. */
+export async function previousItems(): Promise<PreviousItem[] | null> {
+  const file = process.env.GLANCE_APP_PREVIOUS_ITEMS;
+  if (!file) {
+    return null;
+  }
+
+  return JSON.parse(await fs.readFile(file, 'utf-8'));
+}
+
+/** The base URL of the platform's API. */
+export function apiUrl() {
+  return (process.env.GLANCE_API_URL ?? 'http://localhost:6749/api').replace(/\/+$/, '');
+}
+
+/** Something the viewer did with one of the app's items. */
+export interface Feedback {
+  id: number;
+  kind: 'dismiss' | 'undismiss';
+  item_id: string;
+  created_at: string;
+}
+
+/** Fetch the feedback that the viewer has given on the app's items, oldest first. If `sinceId` is
+ * set, only feedback with a greater `id` is returned. */
+export async function fetchFeedback(appId: string, sinceId?: number): Promise<Feedback[]> {
+  const url = new URL(`${apiUrl()}/apps/${encodeURIComponent(appId)}/feedback`);
+  if (sinceId !== undefined) {
+    url.searchParams.set('since_id', sinceId.toString());
+  }
+
+  const res = await fetch(url);
+  if (!res.ok) {
+    throw new Error(`Fetching feedback failed with ${res.status}`);
+  }
+  return res.json();
+}
+
+/** True if the platform is running the app and expects the data on standard output. */
+export function outputToStdout() {
+  return process.env.GLANCE_APP_OUTPUT === 'stdout';
+}
+
 export async function writeAppData(appId: string, appData: AppData) {
+  if (outputToStdout()) {
+    process.stdout.write(JSON.stringify(appData));
+    return;
+  }
+
   const { appDataFile, tmpDataDir } = appPaths(appId);
   const tmpPath = path.join(tmpDataDir, `${appId}-${Date.now()}.json`);
 
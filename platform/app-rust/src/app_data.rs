@@ -13,6 +13,25 @@ pub struct AppData {
     /// The path at which this app is installed
     pub path: String,
 
+    /// The command that runs the app, such as `["bun", "run", "index.ts"]`. When this is not set,
+    /// the platform runs `path`, using an interpreter configured for its extension if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// The directory to run the app in. Defaults to the directory containing `path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    /// How the app returns its data when the platform runs it on a schedule. When this is not
+    /// set, the platform keeps the app's current setting, which starts out as [AppOutput::File].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<AppOutput>,
+
+    /// A URL to which the platform posts each [Feedback](crate::Feedback) on the app's items,
+    /// such as when the viewer dismisses an item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback_webhook: Option<String>,
+
     /// An array of data items that the app is publishing
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub items: Vec<AppItem>,
@@ -24,6 +43,11 @@ pub struct AppData {
     /// Information only used to render the UI of the app
     pub ui: Option<AppUiInfo>,
 
+    /// A JSON Schema describing the settings that the app accepts. Users can set values for these
+    /// settings from the dashboard, and the app can read them using [App::settings](crate::App::settings).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings_schema: Option<BoxedRawValue>,
+
     /// A version number for the app metadata. If this is present, the metadata will only be
     /// updated if the version number in the submitted data is greater than or equal to the number
     /// in the database.
@@ -40,6 +64,31 @@ fn zero() -> u32 {
     0
 }
 
+/// How an app returns its data when the platform runs it on a schedule
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(
+    feature = "sqlx",
+    sqlx(type_name = "app_output", rename_all = "snake_case")
+)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppOutput {
+    /// The app writes its data file into the app data directory, where the platform picks it up.
+    #[default]
+    File,
+    /// The app prints its data as JSON to standard output, and the platform reads it when the
+    /// run finishes.
+    Stdout,
+}
+
+impl AppOutput {
+    /// Return true if the app writes its data file
+    pub fn is_file(&self) -> bool {
+        matches!(self, Self::File)
+    }
+}
+
 /// Information only used to render the UI of the app
 #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -114,7 +163,7 @@ pub struct AppItemData {
 #[cfg(feature = "sqlx")]
 sqlx_json_decode!(AppItemData);
 
-/// A schedule on which to run this app. This is not implemented yet.
+/// A schedule on which the platform runs this app.
 #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct AppSchedule {
@@ -129,6 +178,19 @@ pub struct AppSchedule {
     /// Defaults to 5 minutes, or 300 seconds.
     /// This uses an int instead of a [Duration] for better interoperability with non-Rust apps.
     pub timeout: Option<u32>,
+
+    /// The IANA timezone, such as "America/New_York", in which to evaluate the cron schedule.
+    /// Defaults to UTC.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// The command to run for this schedule, in place of the app's `command`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// The directory to run this schedule in, in place of the app's `working_dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
 }
 
 #[cfg(feature = "sqlx")]
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// What the viewer did with an item
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "event_type"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackKind {
+    /// The viewer dismissed the item
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "dismiss_item"))]
+    Dismiss,
+    /// The viewer brought back an item that was dismissed
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "undismiss_item"))]
+    Undismiss,
+}
+
+/// Something the viewer did with one of the app's items, which the app can use to decide what to
+/// publish next.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Feedback {
+    /// An ID for the feedback, which increases over time
+    pub id: i64,
+    /// What the viewer did
+    pub kind: FeedbackKind,
+    /// The item that the feedback is about
+    pub item_id: String,
+    /// When the viewer gave the feedback
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
+
+#[cfg(feature = "client")]
+impl crate::App {
+    /// Fetch the feedback that the viewer has given on the app's items, oldest first. If
+    /// `since_id` is set, only feedback with a greater [Feedback::id] is returned.
+    pub fn feedback(&self, since_id: Option<i64>) -> Result<Vec<Feedback>, std::io::Error> {
+        let url = format!(
+            "{}/apps/{}/feedback",
+            crate::App::api_url(),
+            percent_encoding::utf8_percent_encode(&self.app_id, percent_encoding::NON_ALPHANUMERIC)
+        );
+        let mut request = ureq::get(&url);
+        if let Some(since_id) = since_id {
+            request = request.query("since_id", &since_id.to_string());
+        }
+
+        request
+            .call()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
+            .into_json()
+    }
+}
@@ -2,14 +2,57 @@
 //! Types for mini-apps that are built for the Glance dashboard platform
 
 mod app_data;
+mod feedback;
 
 use std::path::PathBuf;
 
 pub use app_data::*;
 use etcetera::BaseStrategy;
+pub use feedback::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 #[doc(hidden)]
 pub const APP_DATA_SUBDIR: &'static str = "app_data";
+#[doc(hidden)]
+pub const APP_SETTINGS_SUBDIR: &'static str = "app_settings";
+
+/// The environment variable which holds the app's settings, as JSON, when the platform runs
+/// the app on a schedule.
+pub const SETTINGS_ENV_VAR: &'static str = "GLANCE_APP_SETTINGS";
+
+/// The environment variable which is set to `stdout` when the platform runs the app on a schedule
+/// and expects the app data on standard output. See [AppOutput].
+pub const OUTPUT_ENV_VAR: &'static str = "GLANCE_APP_OUTPUT";
+
+/// The environment variable which holds the base URL of the platform's API. This defaults to
+/// [DEFAULT_API_URL].
+pub const API_URL_ENV_VAR: &'static str = "GLANCE_API_URL";
+
+/// The API URL of a platform running locally with the default settings
+pub const DEFAULT_API_URL: &'static str = "http://localhost:6749/api";
+
+/// The environment variable which holds the path of a JSON file listing the app's current items,
+/// as [PreviousItem]s, when the platform runs the app on a schedule.
+pub const PREVIOUS_ITEMS_ENV_VAR: &'static str = "GLANCE_APP_PREVIOUS_ITEMS";
+
+/// An item that the app published before, as the platform currently has it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PreviousItem {
+    /// The ID of the item
+    pub id: String,
+    /// Display information for the item
+    pub data: AppItemData,
+    /// The state key that the item was published with
+    pub state_key: Option<String>,
+    /// Whether the item can be dismissed by the viewer
+    #[serde(default)]
+    pub persistent: bool,
+    /// True if the viewer has dismissed the item
+    #[serde(default)]
+    pub dismissed: bool,
+    /// When the item was last updated
+    pub updated: chrono::DateTime<chrono::offset::Utc>,
+}
 
 /// Common logic useful for mini-apps
 pub struct App {
@@ -23,6 +66,13 @@ impl App {
         Self { app_id }
     }
 
+    /// The base URL of the platform's API, such as `http://localhost:6749/api`
+    pub fn api_url() -> String {
+        std::env::var(API_URL_ENV_VAR)
+            .map(|url| url.trim_end_matches('/').to_string())
+            .unwrap_or_else(|_| DEFAULT_API_URL.to_string())
+    }
+
     /// The base data directory for the Glance platform
     pub fn base_data_dir() -> PathBuf {
         let p = etcetera::base_strategy::choose_native_strategy()
@@ -46,18 +96,73 @@ impl App {
         Self::base_data_dir().join("tmp")
     }
 
+    /// The directory that holds the settings configured for each app
+    pub fn settings_dir() -> PathBuf {
+        Self::base_data_dir().join(APP_SETTINGS_SUBDIR)
+    }
+
     /// The JSON file that the app should write to
     pub fn data_file(&self) -> PathBuf {
         Self::data_dir().join(format!("{}.json", self.app_id))
     }
 
+    /// The JSON file that holds the settings configured for this app from the dashboard. The
+    /// file will not exist if no settings have been configured.
+    pub fn settings_file(&self) -> PathBuf {
+        Self::settings_dir().join(format!("{}.json", self.app_id))
+    }
+
+    /// Read the settings configured for this app. When the platform runs the app on a schedule the
+    /// settings are passed in [SETTINGS_ENV_VAR]; otherwise they are read from [App::settings_file].
+    /// Returns `None` if no settings have been configured.
+    pub fn settings<T: DeserializeOwned>(&self) -> Result<Option<T>, std::io::Error> {
+        let contents = match std::env::var(SETTINGS_ENV_VAR) {
+            Ok(value) => value,
+            Err(_) => match std::fs::read_to_string(self.settings_file()) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(e),
+            },
+        };
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Read the items that the app published on earlier runs, including whether the viewer has
+    /// dismissed them. These are only available when the platform runs the app on a schedule, and
+    /// this returns `None` otherwise.
+    pub fn previous_items(&self) -> Result<Option<Vec<PreviousItem>>, std::io::Error> {
+        let Some(path) = std::env::var_os(PREVIOUS_ITEMS_ENV_VAR) else {
+            return Ok(None);
+        };
+
+        let contents = std::fs::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
     /// A directory that the app can optionally use to store its internal state.
     pub fn state_dir(&self) -> PathBuf {
         Self::base_data_dir().join("app_state")
     }
 
-    /// Write the app data to the appropriate location
+    /// Return true if the platform is running the app and expects the data on standard output
+    pub fn output_to_stdout() -> bool {
+        std::env::var(OUTPUT_ENV_VAR).is_ok_and(|value| value == "stdout")
+    }
+
+    /// Write the app data to the appropriate location. This prints the data to standard output
+    /// instead when the platform runs the app with [AppOutput::Stdout].
     pub fn write_data(&self, data: &AppData) -> Result<(), std::io::Error> {
+        if Self::output_to_stdout() {
+            let stdout = std::io::stdout().lock();
+            return serde_json::to_writer(stdout, data)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e));
+        }
+
         let tmp_path = Self::tmp_data_dir().join(format!(
             "{}-{}.json",
             self.app_id,
@@ -0,0 +1,4 @@
+ALTER TABLE apps
+  ADD COLUMN validation_errors jsonb;
+
+COMMENT ON COLUMN apps.validation_errors IS 'Structured validation errors from the last rejected update';
@@ -0,0 +1,3 @@
+DROP TABLE rejected_payloads;
+
+DROP TYPE app_data_source;
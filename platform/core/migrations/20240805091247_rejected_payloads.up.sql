@@ -0,0 +1,18 @@
+CREATE TYPE app_data_source AS enum (
+  'file',
+  'http'
+);
+
+CREATE TABLE rejected_payloads (
+  id bigint PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+  -- No foreign key since the rejected payload may be the first one for the app.
+  app_id text NOT NULL,
+  source app_data_source NOT NULL,
+  merge_items boolean NOT NULL DEFAULT FALSE,
+  contents text NOT NULL,
+  error text NOT NULL,
+  validation_errors jsonb,
+  created_at timestamptz NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX ON rejected_payloads (app_id, id DESC);
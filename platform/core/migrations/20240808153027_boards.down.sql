@@ -0,0 +1,8 @@
+DELETE FROM permissions
+WHERE permission IN ('Board::read', 'Board::write', 'Board::owner');
+
+DROP TABLE board_sections;
+
+DROP TABLE boards;
+
+DROP TYPE board_section_size;
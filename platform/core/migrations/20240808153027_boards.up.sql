@@ -0,0 +1,46 @@
+CREATE TYPE board_section_size AS enum (
+  'small',
+  'medium',
+  'large'
+);
+
+CREATE TABLE boards (
+  id uuid NOT NULL PRIMARY KEY,
+  organization_id uuid NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+  -- The user who owns the board, or NULL if the board is shared with the whole organization.
+  user_id uuid REFERENCES users (id) ON DELETE CASCADE,
+  updated_at timestamptz NOT NULL DEFAULT now(),
+  created_at timestamptz NOT NULL DEFAULT now(),
+  name text NOT NULL,
+  description text
+);
+
+CREATE INDEX boards_organization_id ON boards (organization_id, user_id);
+
+CREATE TABLE board_sections (
+  board_id uuid NOT NULL REFERENCES boards (id) ON DELETE CASCADE,
+  position int NOT NULL,
+  title text,
+  -- The apps to show in this section. An empty list means all apps.
+  app_ids text[] NOT NULL DEFAULT '{}',
+  include_dismissed boolean NOT NULL DEFAULT FALSE,
+  persistent boolean,
+  max_items int,
+  columns int,
+  size board_section_size NOT NULL DEFAULT 'medium',
+  PRIMARY KEY (board_id, position)
+);
+
+-- Give existing organizations' user roles access to boards.
+INSERT INTO permissions (organization_id, actor_id, permission)
+SELECT
+  organization_id,
+  actor_id,
+  board_permission
+FROM
+  permissions,
+  unnest(ARRAY['Board::read', 'Board::write', 'Board::owner']) board_permission
+WHERE
+  permission = 'Role::owner'
+ON CONFLICT
+  DO NOTHING;
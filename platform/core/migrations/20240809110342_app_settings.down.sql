@@ -0,0 +1,4 @@
+DROP TABLE app_settings;
+
+ALTER TABLE apps
+  DROP COLUMN settings_schema;
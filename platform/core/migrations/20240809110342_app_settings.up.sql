@@ -0,0 +1,14 @@
+ALTER TABLE apps
+  ADD COLUMN settings_schema jsonb;
+
+CREATE TABLE app_settings (
+  -- No foreign key so that the settings are kept if the app is removed and added again.
+  app_id text NOT NULL,
+  organization_id uuid NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+  -- The user that the values apply to, or NULL for the organization-wide values.
+  user_id uuid REFERENCES users (id) ON DELETE CASCADE,
+  value jsonb NOT NULL,
+  updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX app_settings_app_org_user ON app_settings (app_id, organization_id, user_id) NULLS NOT DISTINCT;
@@ -0,0 +1,3 @@
+ALTER TABLE apps
+  DROP COLUMN enabled,
+  DROP COLUMN schedule;
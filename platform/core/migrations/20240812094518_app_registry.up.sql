@@ -0,0 +1,4 @@
+ALTER TABLE apps
+  ADD COLUMN enabled boolean NOT NULL DEFAULT TRUE,
+  -- The app's requested schedule, kept so that its jobs can be restored when it is re-enabled.
+  ADD COLUMN schedule jsonb NOT NULL DEFAULT '[]';
@@ -0,0 +1,36 @@
+ALTER TABLE apps
+  ADD COLUMN schedule jsonb NOT NULL DEFAULT '[]';
+
+UPDATE
+  apps
+SET
+  schedule = s.schedule
+FROM (
+  SELECT
+    app_id,
+    jsonb_agg(jsonb_build_object('cron', cron, 'arguments', arguments, 'timeout', timeout)
+    ORDER BY position) AS schedule
+  FROM
+    schedules
+  GROUP BY
+    app_id) s
+WHERE
+  apps.id = s.app_id;
+
+DROP INDEX schedules_app_cron_arguments;
+
+-- The old primary key allows only one schedule per cron spec.
+DELETE FROM schedules a USING schedules b
+WHERE a.app_id = b.app_id
+  AND a.cron = b.cron
+  AND a.position > b.position;
+
+ALTER TABLE schedules
+  DROP COLUMN id,
+  DROP COLUMN timeout,
+  DROP COLUMN position,
+  DROP COLUMN created_at,
+  DROP COLUMN updated_at,
+  ALTER COLUMN arguments DROP NOT NULL,
+  ALTER COLUMN arguments DROP DEFAULT,
+  ADD PRIMARY KEY (app_id, cron);
@@ -0,0 +1,38 @@
+-- Give each schedule a stable ID and make this table the source of truth for app schedules,
+-- rather than the app's schedule column and the task queue.
+ALTER TABLE schedules
+  DROP CONSTRAINT schedules_pkey;
+
+-- Nothing wrote to this table before, but clear it so that the new primary key can be added.
+DELETE FROM schedules;
+
+ALTER TABLE schedules
+  ADD COLUMN id uuid PRIMARY KEY,
+  ALTER COLUMN app_id SET NOT NULL,
+  ALTER COLUMN arguments SET NOT NULL,
+  ALTER COLUMN arguments SET DEFAULT '[]'::jsonb,
+  ADD COLUMN timeout int,
+  -- The order of the schedule in the app's data
+  ADD COLUMN position int NOT NULL DEFAULT 0,
+  ADD COLUMN created_at timestamptz NOT NULL DEFAULT now(),
+  ADD COLUMN updated_at timestamptz NOT NULL DEFAULT now();
+
+-- Two schedules with the same cron spec are distinct as long as their arguments differ.
+CREATE UNIQUE INDEX schedules_app_cron_arguments ON schedules (app_id, cron, arguments);
+
+INSERT INTO schedules (id, app_id, cron, arguments, timeout, position)
+SELECT
+  gen_random_uuid(),
+  apps.id,
+  s.value ->> 'cron',
+  COALESCE(s.value -> 'arguments', '[]'::jsonb),
+  (s.value ->> 'timeout')::int,
+  s.ordinality - 1
+FROM
+  apps,
+  jsonb_array_elements(apps.schedule) WITH ORDINALITY AS s (value, ordinality)
+ON CONFLICT
+  DO NOTHING;
+
+ALTER TABLE apps
+  DROP COLUMN schedule;
@@ -0,0 +1,4 @@
+ALTER TABLE schedules
+  DROP COLUMN timezone,
+  DROP COLUMN last_run_at,
+  DROP COLUMN next_run_at;
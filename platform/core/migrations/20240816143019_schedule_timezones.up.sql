@@ -0,0 +1,6 @@
+ALTER TABLE schedules
+  ADD COLUMN timezone text,
+  ADD COLUMN last_run_at timestamptz,
+  -- When a schedule with a timezone should next run. Schedules without a timezone are run by
+  -- recurring jobs in the task queue instead.
+  ADD COLUMN next_run_at timestamptz;
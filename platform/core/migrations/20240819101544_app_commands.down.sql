@@ -0,0 +1,7 @@
+ALTER TABLE apps
+  DROP COLUMN command,
+  DROP COLUMN working_dir;
+
+ALTER TABLE schedules
+  DROP COLUMN command,
+  DROP COLUMN working_dir;
@@ -0,0 +1,7 @@
+ALTER TABLE apps
+  ADD COLUMN command jsonb,
+  ADD COLUMN working_dir text;
+
+ALTER TABLE schedules
+  ADD COLUMN command jsonb,
+  ADD COLUMN working_dir text;
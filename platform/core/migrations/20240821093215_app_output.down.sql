@@ -0,0 +1,21 @@
+ALTER TABLE apps
+  DROP COLUMN output;
+
+DROP TYPE app_output;
+
+-- Postgres can't remove a value from an enum, so recreate the type without it.
+DELETE FROM rejected_payloads
+WHERE source = 'scheduled_run';
+
+ALTER TYPE app_data_source RENAME TO app_data_source_old;
+
+CREATE TYPE app_data_source AS enum (
+  'file',
+  'http'
+);
+
+ALTER TABLE rejected_payloads
+  ALTER COLUMN source TYPE app_data_source
+  USING source::text::app_data_source;
+
+DROP TYPE app_data_source_old;
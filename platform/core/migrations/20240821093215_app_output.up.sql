@@ -0,0 +1,10 @@
+CREATE TYPE app_output AS enum (
+  'file',
+  'stdout'
+);
+
+ALTER TABLE apps
+  ADD COLUMN output app_output NOT NULL DEFAULT 'file';
+
+ALTER TYPE app_data_source
+  ADD VALUE 'scheduled_run';
@@ -0,0 +1,22 @@
+ALTER TABLE apps
+  DROP COLUMN feedback_webhook;
+
+-- Postgres can't remove a value from an enum, so recreate the type without them.
+DELETE FROM events
+WHERE event_type IN ('dismiss_item', 'undismiss_item');
+
+ALTER TYPE event_type RENAME TO event_type_old;
+
+CREATE TYPE event_type AS enum (
+  'create_item',
+  'update_item',
+  'remove_item',
+  'remove_app',
+  'scheduled_run'
+);
+
+ALTER TABLE events
+  ALTER COLUMN event_type TYPE event_type
+  USING event_type::text::event_type;
+
+DROP TYPE event_type_old;
@@ -0,0 +1,8 @@
+ALTER TYPE event_type
+  ADD VALUE 'dismiss_item';
+
+ALTER TYPE event_type
+  ADD VALUE 'undismiss_item';
+
+ALTER TABLE apps
+  ADD COLUMN feedback_webhook text;
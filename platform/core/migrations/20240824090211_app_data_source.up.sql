@@ -0,0 +1,4 @@
+ALTER TABLE apps
+  ADD COLUMN data_source app_data_source;
+
+COMMENT ON COLUMN apps.data_source IS 'Where the app''s most recent data came from. NULL for apps that were installed but have not published any data.';
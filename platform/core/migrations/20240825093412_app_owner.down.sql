@@ -0,0 +1,3 @@
+ALTER TABLE apps
+  DROP COLUMN user_id,
+  DROP COLUMN organization_id;
@@ -0,0 +1,7 @@
+ALTER TABLE apps
+  ADD COLUMN organization_id uuid REFERENCES organizations (id) ON DELETE CASCADE,
+  ADD COLUMN user_id uuid REFERENCES users (id) ON DELETE CASCADE;
+
+COMMENT ON COLUMN apps.organization_id IS 'The organization that owns the app, such as one assigned to the watched directory that the app''s data file is in. NULL for apps without an owner.';
+
+COMMENT ON COLUMN apps.user_id IS 'The user within the organization that owns the app, or NULL if the app belongs to the whole organization.';
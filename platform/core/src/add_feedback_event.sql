@@ -0,0 +1,13 @@
+INSERT INTO events (
+  event_type,
+  app_id,
+  item_id)
+VALUES (
+  $1,
+  $2,
+  $3)
+RETURNING
+  id,
+  event_type AS "kind: FeedbackKind",
+  item_id AS "item_id!",
+  created_at
@@ -0,0 +1,14 @@
+INSERT INTO rejected_payloads (
+  app_id,
+  source,
+  merge_items,
+  contents,
+  error,
+  validation_errors)
+VALUES (
+  $1,
+  $2,
+  $3,
+  $4,
+  $5,
+  $6)
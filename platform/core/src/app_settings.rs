@@ -0,0 +1,53 @@
+use serde::Serialize;
+
+/// The settings for an app, as seen by a particular user
+#[derive(Debug, Serialize)]
+pub struct AppSettings {
+    /// The JSON Schema that the app declared for its settings
+    pub schema: Option<serde_json::Value>,
+    /// The values set for the whole organization
+    pub organization: Option<serde_json::Value>,
+    /// The values set by the user
+    pub user: Option<serde_json::Value>,
+    /// The values that apply to the user, from [merge_settings]
+    pub values: serde_json::Value,
+}
+
+/// Combine organization-wide settings with a user's settings. Each top-level key set by the
+/// user replaces the organization's value for that key.
+pub fn merge_settings(
+    organization: Option<&serde_json::Value>,
+    user: Option<&serde_json::Value>,
+) -> serde_json::Value {
+    match (organization, user) {
+        (Some(serde_json::Value::Object(org)), Some(serde_json::Value::Object(user))) => {
+            let mut merged = org.clone();
+            merged.extend(user.iter().map(|(k, v)| (k.clone(), v.clone())));
+            serde_json::Value::Object(merged)
+        }
+        (_, Some(user)) => user.clone(),
+        (Some(org), None) => org.clone(),
+        (None, None) => serde_json::Value::Object(Default::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let org = json!({ "lat": 1, "lon": 2 });
+        let user = json!({ "lon": 3, "units": "metric" });
+
+        assert_eq!(
+            merge_settings(Some(&org), Some(&user)),
+            json!({ "lat": 1, "lon": 3, "units": "metric" })
+        );
+        assert_eq!(merge_settings(Some(&org), None), org);
+        assert_eq!(merge_settings(None, Some(&user)), user);
+        assert_eq!(merge_settings(None, None), json!({}));
+    }
+}
@@ -0,0 +1,29 @@
+SELECT
+  id,
+  name,
+  path,
+  ui,
+  version,
+  settings_schema,
+  enabled,
+  command,
+  working_dir,
+  output AS "output: AppOutput",
+  feedback_webhook,
+  builtin_source AS "builtin_source: Json<BuiltinSource>",
+  data_source AS "data_source: AppDataSource",
+  organization_id AS "organization_id: OrganizationId",
+  user_id AS "user_id: UserId",
+  COALESCE((
+    SELECT
+      jsonb_agg(jsonb_build_object('cron', cron, 'arguments', arguments, 'timeout', timeout,
+        'timezone', timezone, 'command', command, 'working_dir', working_dir)
+      ORDER BY position)
+    FROM schedules
+    WHERE
+      schedules.app_id = apps.id), '[]'::jsonb) AS "schedule!: Json<Vec<AppSchedule>>",
+  updated_at
+FROM
+  apps
+ORDER BY
+  id
@@ -0,0 +1,10 @@
+SELECT
+  event_type::text AS "event_type!",
+  app_id,
+  item_id,
+  metadata,
+  created_at
+FROM
+  events
+ORDER BY
+  id
@@ -0,0 +1,14 @@
+SELECT
+  id,
+  app_id,
+  data,
+  state_key,
+  persistent,
+  dismissed,
+  created_at,
+  updated_at
+FROM
+  items
+ORDER BY
+  app_id,
+  id
@@ -0,0 +1,10 @@
+SELECT
+  id,
+  item_id,
+  app_id,
+  data,
+  dismissed
+FROM
+  item_notifications
+ORDER BY
+  id
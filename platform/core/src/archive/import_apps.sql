@@ -0,0 +1,54 @@
+INSERT INTO apps (
+  id,
+  name,
+  path,
+  ui,
+  version,
+  settings_schema,
+  enabled,
+  command,
+  working_dir,
+  output,
+  feedback_webhook,
+  builtin_source,
+  data_source,
+  organization_id,
+  user_id,
+  updated_at)
+SELECT
+  id,
+  name,
+  path,
+  ui,
+  version,
+  settings_schema,
+  enabled,
+  command,
+  working_dir,
+  output,
+  feedback_webhook,
+  builtin_source,
+  data_source,
+  organization_id,
+  user_id,
+  updated_at
+FROM
+  jsonb_populate_recordset(NULL::apps, $1)
+ON CONFLICT (
+  id)
+  DO UPDATE SET
+    name = EXCLUDED.name,
+    path = EXCLUDED.path,
+    ui = EXCLUDED.ui,
+    version = EXCLUDED.version,
+    settings_schema = EXCLUDED.settings_schema,
+    enabled = EXCLUDED.enabled,
+    command = EXCLUDED.command,
+    working_dir = EXCLUDED.working_dir,
+    output = EXCLUDED.output,
+    feedback_webhook = EXCLUDED.feedback_webhook,
+    builtin_source = EXCLUDED.builtin_source,
+    data_source = EXCLUDED.data_source,
+    organization_id = EXCLUDED.organization_id,
+    user_id = EXCLUDED.user_id,
+    updated_at = EXCLUDED.updated_at
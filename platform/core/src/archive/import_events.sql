@@ -0,0 +1,27 @@
+-- Event IDs are generated, so an event is considered already present when an event with the
+-- same contents and time exists.
+INSERT INTO events (
+  event_type,
+  app_id,
+  item_id,
+  metadata,
+  created_at)
+SELECT
+  input.event_type,
+  input.app_id,
+  input.item_id,
+  input.metadata,
+  input.created_at
+FROM
+  jsonb_populate_recordset(NULL::events, $1) AS input
+WHERE
+  NOT EXISTS (
+    SELECT
+      1
+    FROM
+      events
+    WHERE
+      events.event_type = input.event_type
+      AND events.app_id = input.app_id
+      AND events.item_id IS NOT DISTINCT FROM input.item_id
+      AND events.created_at = input.created_at)
@@ -0,0 +1,35 @@
+INSERT INTO items (
+  id,
+  app_id,
+  data,
+  state_key,
+  persistent,
+  dismissed,
+  created_at,
+  updated_at)
+SELECT
+  id,
+  app_id,
+  data,
+  state_key,
+  persistent,
+  dismissed,
+  created_at,
+  updated_at
+FROM
+  jsonb_populate_recordset(NULL::items, $1)
+ON CONFLICT (
+  app_id,
+  id)
+  DO UPDATE SET
+    data = EXCLUDED.data,
+    state_key = EXCLUDED.state_key,
+    persistent = EXCLUDED.persistent,
+    dismissed = EXCLUDED.dismissed,
+    created_at = EXCLUDED.created_at,
+    updated_at = EXCLUDED.updated_at
+  -- Skip rows that already match so that importing the same archive again doesn't add
+  -- update_item events.
+  WHERE (items.data, items.state_key, items.persistent, items.dismissed, items.created_at,
+    items.updated_at) IS DISTINCT FROM (EXCLUDED.data, EXCLUDED.state_key, EXCLUDED.persistent,
+    EXCLUDED.dismissed, EXCLUDED.created_at, EXCLUDED.updated_at)
@@ -0,0 +1,21 @@
+INSERT INTO item_notifications (
+  id,
+  item_id,
+  app_id,
+  data,
+  dismissed)
+SELECT
+  id,
+  item_id,
+  app_id,
+  data,
+  dismissed
+FROM
+  jsonb_populate_recordset(NULL::item_notifications, $1)
+ON CONFLICT (
+  id)
+  DO UPDATE SET
+    item_id = EXCLUDED.item_id,
+    app_id = EXCLUDED.app_id,
+    data = EXCLUDED.data,
+    dismissed = EXCLUDED.dismissed
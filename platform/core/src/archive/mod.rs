@@ -0,0 +1,347 @@
+//! Export the dashboard state to a JSON archive and restore it.
+
+use error_stack::{Report, ResultExt};
+use glance_app::{AppOutput, AppSchedule};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use tracing::instrument;
+
+use crate::{
+    builtin_sources::BuiltinSource,
+    db::DbInner,
+    models::{organization::OrganizationId, user::UserId},
+    scheduled_task::AppLaunch,
+    AppDataSource, Error,
+};
+
+/// The archive format version written by this build. Bump this when the format changes in a way
+/// that older builds can't read.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// A snapshot of the apps, items, and notifications in the database.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    /// The format version of the archive
+    pub version: u32,
+    /// When the archive was created
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    /// The apps, including their schedules
+    pub apps: Vec<ArchiveApp>,
+    /// Items for all apps, including dismissed items
+    pub items: Vec<ArchiveItem>,
+    /// Item notifications
+    pub notifications: Vec<ArchiveNotification>,
+    /// The event log, if it was requested in the export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<ArchiveEvent>>,
+}
+
+/// An app in an [Archive]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveApp {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub ui: serde_json::Value,
+    pub version: i64,
+    pub settings_schema: Option<serde_json::Value>,
+    pub enabled: bool,
+    pub command: Option<Json<Vec<String>>>,
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub output: AppOutput,
+    pub feedback_webhook: Option<String>,
+    #[serde(default)]
+    pub builtin_source: Option<Json<BuiltinSource>>,
+    #[serde(default)]
+    pub data_source: Option<AppDataSource>,
+    /// The organization that owns the app, if any
+    #[serde(default)]
+    pub organization_id: Option<OrganizationId>,
+    /// The user within the organization that owns the app, if any
+    #[serde(default)]
+    pub user_id: Option<UserId>,
+    pub schedule: Json<Vec<AppSchedule>>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An item in an [Archive]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveItem {
+    pub id: String,
+    pub app_id: String,
+    pub data: serde_json::Value,
+    pub state_key: Option<String>,
+    pub persistent: bool,
+    pub dismissed: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An item notification in an [Archive]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveNotification {
+    pub id: String,
+    pub item_id: Option<String>,
+    pub app_id: Option<String>,
+    pub data: serde_json::Value,
+    pub dismissed: bool,
+}
+
+/// An event in an [Archive]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveEvent {
+    pub event_type: String,
+    pub app_id: String,
+    pub item_id: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Counts of the records that an import added or changed
+#[derive(Debug)]
+pub struct ImportSummary {
+    pub apps: u64,
+    pub items: u64,
+    pub notifications: u64,
+    pub events: u64,
+}
+
+impl DbInner {
+    /// Read the current dashboard state into an archive.
+    #[instrument(skip(self))]
+    pub async fn export_archive(&self, include_events: bool) -> Result<Archive, Report<Error>> {
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+
+        let apps = sqlx::query_file_as!(ArchiveApp, "src/archive/export_apps.sql")
+            .fetch_all(&mut *tx)
+            .await
+            .change_context(Error::Db)?;
+        let items = sqlx::query_file_as!(ArchiveItem, "src/archive/export_items.sql")
+            .fetch_all(&mut *tx)
+            .await
+            .change_context(Error::Db)?;
+        let notifications =
+            sqlx::query_file_as!(ArchiveNotification, "src/archive/export_notifications.sql")
+                .fetch_all(&mut *tx)
+                .await
+                .change_context(Error::Db)?;
+        let events = if include_events {
+            let events = sqlx::query_file_as!(ArchiveEvent, "src/archive/export_events.sql")
+                .fetch_all(&mut *tx)
+                .await
+                .change_context(Error::Db)?;
+            Some(events)
+        } else {
+            None
+        };
+
+        tx.commit().await.change_context(Error::Db)?;
+
+        Ok(Archive {
+            version: ARCHIVE_VERSION,
+            exported_at: chrono::Utc::now(),
+            apps,
+            items,
+            notifications,
+            events,
+        })
+    }
+
+    /// Restore an archive. Existing records with the same IDs are overwritten and everything
+    /// else in the database is left alone, so importing the same archive more than once has no
+    /// further effect.
+    #[instrument(skip_all)]
+    pub async fn import_archive(&self, archive: &Archive) -> Result<ImportSummary, Report<Error>> {
+        if archive.version > ARCHIVE_VERSION {
+            return Err(Report::new(Error::InvalidArchive)).attach_printable(format!(
+                "Archive version {} is newer than the supported version {ARCHIVE_VERSION}",
+                archive.version
+            ));
+        }
+
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+
+        let apps = sqlx::query_file!("src/archive/import_apps.sql", to_json(&archive.apps)?)
+            .execute(&mut *tx)
+            .await
+            .change_context(Error::Db)?
+            .rows_affected();
+
+        let mut schedules = Vec::with_capacity(archive.apps.len());
+        for app in &archive.apps {
+            let app_schedules = self
+                .write_app_schedules(&mut *tx, &app.id, &app.schedule.0)
+                .await?;
+            schedules.push((app, app_schedules));
+        }
+
+        // Restore the events before the items, so that the events generated by inserting the
+        // items come after the old ones.
+        let events = match &archive.events {
+            Some(events) => sqlx::query_file!("src/archive/import_events.sql", to_json(events)?)
+                .execute(&mut *tx)
+                .await
+                .change_context(Error::Db)?
+                .rows_affected(),
+            None => 0,
+        };
+
+        let items = sqlx::query_file!("src/archive/import_items.sql", to_json(&archive.items)?)
+            .execute(&mut *tx)
+            .await
+            .change_context(Error::Db)?
+            .rows_affected();
+
+        let notifications = sqlx::query_file!(
+            "src/archive/import_notifications.sql",
+            to_json(&archive.notifications)?
+        )
+        .execute(&mut *tx)
+        .await
+        .change_context(Error::Db)?
+        .rows_affected();
+
+        tx.commit().await.change_context(Error::Db)?;
+
+        // The task queue lives outside the database, so bring it in line once the data is
+        // committed.
+        for (app, app_schedules) in schedules {
+            let launch = AppLaunch {
+                path: app.path.clone(),
+                command: app.command.clone(),
+                working_dir: app.working_dir.clone(),
+                output: app.output,
+                builtin_source: app.builtin_source.clone(),
+            };
+            self.sync_scheduled_jobs(&app.id, app.enabled.then_some(&launch), &app_schedules)
+                .await?;
+        }
+
+        Ok(ImportSummary {
+            apps,
+            items,
+            notifications,
+            events,
+        })
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value, Report<Error>> {
+    serde_json::to_value(value).change_context(Error::InvalidArchive)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        db::AppVisibility,
+        models::organization::OrganizationId,
+        tests::platform::{app_data, TestPlatform},
+        AppDataSource, AppFileContents, AppFileInput, AppOwner,
+    };
+
+    #[sqlx::test]
+    async fn export_and_import(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let data = app_data(json!({
+            "name": "App",
+            "path": "/bin/app",
+            "schedule": [{ "cron": "0 0 * * * *", "arguments": ["--fast"] }],
+            "items": [
+                { "id": "a", "data": { "title": "A" }, "updated": "2024-01-01T00:00:00Z" },
+                { "id": "b", "data": { "title": "B" }, "updated": "2024-01-01T00:00:00Z" }
+            ]
+        }));
+        assert!(platform.send_app_data("app", data, false).await);
+
+        let db = platform.platform.db.clone();
+        db.set_item_dismissed("app", "b", true).await.unwrap();
+
+        let archive = db.export_archive(true).await.unwrap();
+        assert_eq!(archive.apps.len(), 1);
+        assert_eq!(archive.items.len(), 2);
+        assert!(!archive.events.as_ref().unwrap().is_empty());
+
+        // Round trip through the serialized form, as the CLI does.
+        let archive = serde_json::from_slice(&serde_json::to_vec(&archive).unwrap()).unwrap();
+
+        db.remove_app("app").await.unwrap();
+        assert!(db.read_app_items("app").await.unwrap().is_empty());
+
+        let summary = db.import_archive(&archive).await.unwrap();
+        assert_eq!(summary.apps, 1);
+        assert_eq!(summary.items, 2);
+
+        let mut items = db.read_app_items("app").await.unwrap();
+        items.sort_by(|a, b| a.id.cmp(&b.id));
+        assert_eq!(items.len(), 2);
+        assert!(!items[0].dismissed);
+        assert!(items[1].dismissed);
+
+        let schedules = db.get_app_schedules(&db.pool, "app").await.unwrap();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].arguments.0, vec!["--fast"]);
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("app:")
+            .await
+            .unwrap();
+        assert_eq!(jobs, vec![schedules[0].job_id()]);
+
+        // Importing again changes nothing.
+        let summary = db.import_archive(&archive).await.unwrap();
+        assert_eq!(summary.items, 0);
+        assert_eq!(summary.events, 0);
+    }
+
+    #[sqlx::test]
+    async fn export_and_import_owner(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::new(pool.clone()).await;
+        let organization_id = OrganizationId::new();
+        sqlx::query!(
+            "INSERT INTO organizations (id, name) VALUES ($1, 'Owner')",
+            organization_id.as_uuid()
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let data = app_data(json!({ "name": "App", "path": "/bin/app", "items": [] }));
+        platform
+            .platform
+            .change_tx
+            .send_async(AppFileInput {
+                app_id: "app".to_string(),
+                contents: AppFileContents::Parsed(Box::new(data)),
+                merge_items: false,
+                source: AppDataSource::File,
+                owner: Some(AppOwner {
+                    organization_id,
+                    user_id: None,
+                }),
+            })
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("app").await);
+
+        let db = platform.platform.db.clone();
+        let archive = db.export_archive(false).await.unwrap();
+        assert_eq!(archive.apps[0].organization_id, Some(organization_id));
+        assert!(archive.apps[0].user_id.is_none());
+
+        let archive = serde_json::from_slice(&serde_json::to_vec(&archive).unwrap()).unwrap();
+        db.remove_app("app").await.unwrap();
+        db.import_archive(&archive).await.unwrap();
+
+        // The restored app is still hidden from people outside the organization.
+        let app_ids = ["app".to_string()];
+        let visible = db
+            .get_visible_apps(&app_ids, &AppVisibility::Viewer(None))
+            .await
+            .unwrap();
+        assert!(visible.is_empty());
+        assert_eq!(db.get_apps(&app_ids).await.unwrap().len(), 1);
+    }
+}
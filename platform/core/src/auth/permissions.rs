@@ -56,6 +56,21 @@ pub const PERMISSIONS: &[PermissionInfo] = &[
         description: "Create and delete User objects",
         key: "User::owner",
     },
+    PermissionInfo {
+        name: "Read Boards",
+        description: "List and read Board objects",
+        key: "Board::read",
+    },
+    PermissionInfo {
+        name: "Write Boards",
+        description: "Write Board objects",
+        key: "Board::write",
+    },
+    PermissionInfo {
+        name: "Administer Boards",
+        description: "Create and delete Board objects",
+        key: "Board::owner",
+    },
 ];
 
 pub async fn list_permissions(_authed: Authed) -> impl IntoResponse {
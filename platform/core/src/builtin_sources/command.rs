@@ -0,0 +1,257 @@
+use error_stack::{Report, ResultExt};
+use glance_app::{AppItem, AppItemData};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+
+use crate::{error::Error, validation::ValidationIssue};
+
+/// Publish the output of a shell command, which runs on the app's schedules like any other
+/// scheduled app.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommandSource {
+    /// The command to run with `sh -c`
+    pub command: String,
+    /// How to read the command's output
+    #[serde(default)]
+    pub format: CommandFormat,
+    /// How to give each item its ID
+    #[serde(default)]
+    pub id: CommandItemId,
+}
+
+/// How to turn a command's output into items
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandFormat {
+    /// Each non-empty line of output is an item, with the line as its title
+    #[default]
+    Lines,
+    /// The output is a JSON array of item data objects, with at least a `title`
+    Json,
+}
+
+/// Where an item from a command's output gets its ID
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandItemId {
+    /// The item's title, so that the same line keeps the same item
+    #[default]
+    Title,
+    /// The item's position in the output
+    Position,
+    /// A field of each JSON object
+    Field(String),
+}
+
+impl std::str::FromStr for CommandItemId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "position" => Ok(Self::Position),
+            _ => match s.strip_prefix("field:") {
+                Some(field) if !field.is_empty() => Ok(Self::Field(field.to_string())),
+                _ => Err(format!(
+                    "Expected title, position, or field:NAME, but got {s:?}"
+                )),
+            },
+        }
+    }
+}
+
+impl CommandSource {
+    /// The command line that runs the command.
+    pub fn app_command(&self) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), self.command.clone()]
+    }
+
+    pub(super) fn validate(&self, issues: &mut Vec<ValidationIssue>) {
+        if self.command.trim().is_empty() {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/command",
+                "The command must not be empty",
+            ));
+        }
+
+        if self.format == CommandFormat::Lines && matches!(self.id, CommandItemId::Field(_)) {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/id",
+                "Field IDs can only be used with JSON output",
+            ));
+        }
+    }
+
+    /// Convert the command's output into items.
+    pub fn parse_output(&self, output: &str) -> Result<Vec<AppItem>, Report<Error>> {
+        let now = chrono::Utc::now();
+        let entries = match self.format {
+            CommandFormat::Lines => output
+                .lines()
+                .map(str::trim_end)
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| (None, item_data(line.to_string())))
+                .collect::<Vec<_>>(),
+            CommandFormat::Json => {
+                let elements = serde_json::from_str::<Vec<Box<RawValue>>>(output)
+                    .change_context(Error::ReadAppData)
+                    .attach_printable("The output is not a JSON array")?;
+                elements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, element)| {
+                        self.json_entry(element)
+                            .attach_printable(format!("Item {i}"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+        };
+
+        let items = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, (field_id, data))| {
+                let id = match &self.id {
+                    CommandItemId::Title => data.title.clone(),
+                    CommandItemId::Position => i.to_string(),
+                    CommandItemId::Field(_) => field_id.unwrap_or_default(),
+                };
+                AppItem {
+                    id,
+                    state_key: None,
+                    data,
+                    persistent: false,
+                    notify: Vec::new(),
+                    updated: now,
+                }
+            })
+            // Repeated lines would otherwise be duplicate items.
+            .unique_by(|item| item.id.clone())
+            .collect();
+        Ok(items)
+    }
+
+    /// Read one element of JSON output, along with its ID field if the ID comes from a field.
+    fn json_entry(
+        &self,
+        element: &RawValue,
+    ) -> Result<(Option<String>, AppItemData), Report<Error>> {
+        let data = serde_json::from_str::<AppItemData>(element.get())
+            .change_context(Error::ReadAppData)?;
+
+        let CommandItemId::Field(field) = &self.id else {
+            return Ok((None, data));
+        };
+
+        let value = serde_json::from_str::<serde_json::Value>(element.get())
+            .change_context(Error::ReadAppData)?;
+        let id = match value.get(field) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Number(n)) => n.to_string(),
+            _ => {
+                return Err(Report::new(Error::ReadAppData))
+                    .attach_printable(format!("Missing ID field {field:?}"))
+            }
+        };
+        Ok((Some(id), data))
+    }
+}
+
+fn item_data(title: String) -> AppItemData {
+    AppItemData {
+        title,
+        subtitle: None,
+        detail: None,
+        url: None,
+        icon: None,
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(format: CommandFormat, id: CommandItemId) -> CommandSource {
+        CommandSource {
+            command: "true".to_string(),
+            format,
+            id,
+        }
+    }
+
+    fn ids(items: &[AppItem]) -> Vec<&str> {
+        items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn lines() {
+        let output = "sshd.service failed\n\ncron.service failed   \nsshd.service failed\n";
+
+        let items = source(CommandFormat::Lines, CommandItemId::Title)
+            .parse_output(output)
+            .unwrap();
+        assert_eq!(
+            ids(&items),
+            vec!["sshd.service failed", "cron.service failed"]
+        );
+        assert_eq!(items[1].data.title, "cron.service failed");
+
+        let items = source(CommandFormat::Lines, CommandItemId::Position)
+            .parse_output(output)
+            .unwrap();
+        assert_eq!(ids(&items), vec!["0", "1", "2"]);
+    }
+
+    #[test]
+    fn json() {
+        let output = r#"[
+            { "mount": "/", "title": "/ is 91% full", "detail": "4.2G free" },
+            { "mount": "/home", "title": "/home is 40% full", "url": null }
+        ]"#;
+
+        let items = source(
+            CommandFormat::Json,
+            CommandItemId::Field("mount".to_string()),
+        )
+        .parse_output(output)
+        .unwrap();
+        assert_eq!(ids(&items), vec!["/", "/home"]);
+        assert_eq!(items[0].data.title, "/ is 91% full");
+        assert_eq!(items[0].data.detail.as_deref(), Some("4.2G free"));
+
+        source(
+            CommandFormat::Json,
+            CommandItemId::Field("missing".to_string()),
+        )
+        .parse_output(output)
+        .expect_err("missing ID field");
+        source(CommandFormat::Json, CommandItemId::Title)
+            .parse_output("not json")
+            .expect_err("invalid JSON");
+    }
+
+    #[test]
+    fn parse_item_id() {
+        assert_eq!("title".parse(), Ok(CommandItemId::Title));
+        assert_eq!("position".parse(), Ok(CommandItemId::Position));
+        assert_eq!(
+            "field:unit".parse(),
+            Ok(CommandItemId::Field("unit".to_string()))
+        );
+        assert!("field:".parse::<CommandItemId>().is_err());
+    }
+
+    #[test]
+    fn field_ids_need_json() {
+        let mut issues = Vec::new();
+        source(
+            CommandFormat::Lines,
+            CommandItemId::Field("unit".to_string()),
+        )
+        .validate(&mut issues);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "/builtin_source/id");
+    }
+}
@@ -0,0 +1,280 @@
+use std::time::Duration;
+
+use error_stack::{Report, ResultExt};
+use feed_rs::model::{Entry, Feed};
+use glance_app::{AppItem, AppItemData};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{error::Error, validation::ValidationIssue};
+
+/// How many entries to show from each feed by default
+const DEFAULT_MAX_ITEMS: usize = 20;
+
+/// How long to wait for a feed to respond
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Publish the entries from RSS or Atom feeds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeedSource {
+    /// The URLs of the feeds
+    pub urls: Vec<String>,
+    /// How many entries to show from each feed, starting from the top of the feed
+    #[serde(default = "default_max_items")]
+    pub max_items: usize,
+}
+
+fn default_max_items() -> usize {
+    DEFAULT_MAX_ITEMS
+}
+
+impl FeedSource {
+    /// Publish the entries from `urls`, with the default number of entries from each.
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            max_items: DEFAULT_MAX_ITEMS,
+        }
+    }
+
+    pub(super) fn validate(&self, issues: &mut Vec<ValidationIssue>) {
+        if self.urls.is_empty() {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/urls",
+                "At least one feed URL is required",
+            ));
+        }
+
+        for (i, url) in self.urls.iter().enumerate() {
+            if let Err(e) = Url::parse(url) {
+                issues.push(ValidationIssue::new(
+                    format!("/builtin_source/urls/{i}"),
+                    format!("Invalid URL {url:?}: {e}"),
+                ));
+            }
+        }
+    }
+
+    /// Fetch every feed and convert the entries into items. This fails if any feed fails, so that
+    /// a temporary error with one feed doesn't remove its items.
+    pub(super) async fn fetch(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<Vec<AppItem>, Report<Error>> {
+        let fetched_at = chrono::Utc::now();
+        let feeds =
+            futures::future::try_join_all(self.urls.iter().map(|url| fetch_feed(client, url)))
+                .await?;
+
+        let items = feeds
+            .into_iter()
+            .flat_map(|(base, feed)| {
+                let feed_title = feed.title.map(|title| title.content);
+                feed.entries
+                    .into_iter()
+                    .take(self.max_items)
+                    .map(move |entry| entry_item(&base, feed_title.as_deref(), entry, fetched_at))
+            })
+            // The same entry can show up in more than one feed.
+            .unique_by(|item| item.id.clone())
+            .collect();
+        Ok(items)
+    }
+}
+
+async fn fetch_feed(client: &reqwest::Client, url: &str) -> Result<(Url, Feed), Report<Error>> {
+    let base = Url::parse(url)
+        .change_context(Error::FetchSource)
+        .attach_printable_lazy(|| format!("Invalid feed URL {url}"))?;
+    let body = client
+        .get(base.clone())
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .change_context(Error::FetchSource)
+        .attach_printable_lazy(|| format!("Fetching {url}"))?
+        .bytes()
+        .await
+        .change_context(Error::FetchSource)
+        .attach_printable_lazy(|| format!("Reading {url}"))?;
+
+    let feed = feed_rs::parser::parse(body.as_ref())
+        .change_context(Error::FetchSource)
+        .attach_printable_lazy(|| format!("Parsing feed from {url}"))?;
+    Ok((base, feed))
+}
+
+/// Convert a feed entry into an item, using the entry's ID as the state key so that edits to an
+/// entry don't bring it back after it was dismissed.
+fn entry_item(
+    base: &Url,
+    feed_title: Option<&str>,
+    entry: Entry,
+    fetched_at: chrono::DateTime<chrono::Utc>,
+) -> AppItem {
+    // Prefer the entry's main link over related links like comments or enclosures.
+    let link = entry
+        .links
+        .iter()
+        .find(|link| link.rel.as_deref().map_or(true, |rel| rel == "alternate"))
+        .or(entry.links.first());
+    // Links can be relative to the feed.
+    let url = link
+        .and_then(|link| base.join(&link.href).ok())
+        .map(String::from);
+
+    let detail = entry
+        .summary
+        .map(|summary| summary.content)
+        .or_else(|| entry.content.and_then(|content| content.body));
+
+    AppItem {
+        id: entry.id.clone(),
+        state_key: Some(entry.id),
+        data: AppItemData {
+            title: entry
+                .title
+                .map(|title| title.content)
+                .unwrap_or_else(|| "Untitled".to_string()),
+            subtitle: feed_title.map(String::from),
+            detail,
+            url,
+            icon: None,
+            data: None,
+        },
+        persistent: false,
+        notify: Vec::new(),
+        updated: entry.updated.or(entry.published).unwrap_or(fetched_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::header, routing::get, Router};
+
+    use super::*;
+    use crate::tests::serve_stub;
+
+    const RSS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0">
+  <channel>
+    <title>Example News</title>
+    <link>https://news.example.com/</link>
+    <description>News</description>
+    <item>
+      <guid>news-2</guid>
+      <title>Second story</title>
+      <link>https://news.example.com/2</link>
+      <description>The second story</description>
+      <pubDate>Tue, 02 Jan 2024 12:00:00 GMT</pubDate>
+    </item>
+    <item>
+      <guid>news-1</guid>
+      <title>First story</title>
+      <link>/1</link>
+      <pubDate>Mon, 01 Jan 2024 12:00:00 GMT</pubDate>
+    </item>
+  </channel>
+</rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Example Blog</title>
+  <id>urn:example:blog</id>
+  <updated>2024-01-03T00:00:00Z</updated>
+  <entry>
+    <id>urn:example:blog:post-1</id>
+    <title>A post</title>
+    <link rel="replies" href="https://blog.example.com/post-1/comments"/>
+    <link rel="alternate" href="https://blog.example.com/post-1"/>
+    <updated>2024-01-03T00:00:00Z</updated>
+    <summary>About the post</summary>
+  </entry>
+</feed>"#;
+
+    async fn feed_server() -> String {
+        serve_stub(
+            Router::new()
+                .route(
+                    "/rss",
+                    get(|| async { ([(header::CONTENT_TYPE, "application/rss+xml")], RSS) }),
+                )
+                .route(
+                    "/atom",
+                    get(|| async { ([(header::CONTENT_TYPE, "application/atom+xml")], ATOM) }),
+                ),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn rss_and_atom() {
+        let base = feed_server().await;
+        let source = FeedSource::new(vec![format!("{base}/rss"), format!("{base}/atom")]);
+        let items = source.fetch(&reqwest::Client::new()).await.unwrap();
+
+        let ids = items
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["news-2", "news-1", "urn:example:blog:post-1"]);
+        for item in &items {
+            assert_eq!(item.state_key.as_deref(), Some(item.id.as_str()));
+        }
+
+        assert_eq!(items[0].data.title, "Second story");
+        assert_eq!(items[0].data.subtitle.as_deref(), Some("Example News"));
+        assert_eq!(items[0].data.detail.as_deref(), Some("The second story"));
+        assert_eq!(
+            items[0].data.url.as_deref(),
+            Some("https://news.example.com/2")
+        );
+        assert_eq!(items[0].updated.to_rfc3339(), "2024-01-02T12:00:00+00:00");
+
+        // Relative links are resolved against the feed URL.
+        assert_eq!(items[1].data.url, Some(format!("{base}/1")));
+
+        assert_eq!(items[2].data.title, "A post");
+        assert_eq!(
+            items[2].data.url.as_deref(),
+            Some("https://blog.example.com/post-1")
+        );
+        assert_eq!(items[2].data.detail.as_deref(), Some("About the post"));
+    }
+
+    #[tokio::test]
+    async fn max_items_and_duplicates() {
+        let base = feed_server().await;
+        let mut source = FeedSource::new(vec![format!("{base}/rss"), format!("{base}/rss")]);
+        source.max_items = 1;
+        let items = source.fetch(&reqwest::Client::new()).await.unwrap();
+
+        let ids = items
+            .iter()
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["news-2"]);
+    }
+
+    #[tokio::test]
+    async fn any_failed_feed_fails_the_fetch() {
+        let base = feed_server().await;
+        let source = FeedSource::new(vec![format!("{base}/rss"), format!("{base}/missing")]);
+        let err = source.fetch(&reqwest::Client::new()).await.unwrap_err();
+        assert!(matches!(err.current_context(), Error::FetchSource));
+    }
+
+    #[test]
+    fn validation() {
+        let mut issues = Vec::new();
+        FeedSource::new(vec![]).validate(&mut issues);
+        FeedSource::new(vec!["not a url".to_string()]).validate(&mut issues);
+        let paths = issues.iter().map(|i| i.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec!["/builtin_source/urls", "/builtin_source/urls/0"]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use error_stack::{Report, ResultExt};
+use glance_app::{AppItem, AppItemData};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{error::Error, validation::ValidationIssue};
+
+/// How long to wait for the endpoint to respond
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Publish items from a JSON endpoint, using templates to map each element of an array in the
+/// response to an item.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JsonSource {
+    /// The URL to GET
+    pub url: String,
+    /// Extra headers to send with the request, such as an API key
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// A JSON pointer to the array of items in the response, such as `/data/results`. The
+    /// whole response is used when this is empty.
+    #[serde(default)]
+    pub items: String,
+    /// How to build each item
+    pub fields: JsonFieldTemplates,
+}
+
+/// Tera templates that build an item's fields. Each template can use the array element as
+/// `item`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JsonFieldTemplates {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl JsonFieldTemplates {
+    fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("id", Some(&self.id)),
+            ("title", Some(&self.title)),
+            ("subtitle", self.subtitle.as_ref()),
+            ("detail", self.detail.as_ref()),
+            ("url", self.url.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, template)| Some((name, template?.as_str())))
+    }
+
+    fn compile(&self) -> Result<tera::Tera, tera::Error> {
+        let mut tera = tera::Tera::default();
+        tera.add_raw_templates(self.iter())?;
+        Ok(tera)
+    }
+}
+
+impl JsonSource {
+    pub(super) fn validate(&self, issues: &mut Vec<ValidationIssue>) {
+        if let Err(e) = Url::parse(&self.url) {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/url",
+                format!("Invalid URL {:?}: {e}", self.url),
+            ));
+        }
+
+        for (name, value) in &self.headers {
+            let valid = http::HeaderName::try_from(name.as_str()).is_ok()
+                && http::HeaderValue::try_from(value.as_str()).is_ok();
+            if !valid {
+                issues.push(ValidationIssue::new(
+                    format!("/builtin_source/headers/{name}"),
+                    "Invalid header",
+                ));
+            }
+        }
+
+        if !self.items.is_empty() && !self.items.starts_with('/') {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/items",
+                "The items location must be a JSON pointer starting with /",
+            ));
+        }
+
+        // Check each template on its own so that every broken one is reported.
+        for (name, template) in self.fields.iter() {
+            if let Err(e) = tera::Tera::default().add_raw_template(name, template) {
+                let message = std::error::Error::source(&e)
+                    .map(|source| source.to_string())
+                    .unwrap_or_else(|| e.to_string());
+                issues.push(ValidationIssue::new(
+                    format!("/builtin_source/fields/{name}"),
+                    message,
+                ));
+            }
+        }
+    }
+
+    /// Fetch the endpoint and build an item from each element of the item array.
+    pub(super) async fn fetch(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<Vec<AppItem>, Report<Error>> {
+        let tera = self
+            .fields
+            .compile()
+            .change_context(Error::FetchSource)
+            .attach_printable("Compiling field templates")?;
+
+        let mut request = client.get(&self.url).timeout(FETCH_TIMEOUT);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response: serde_json::Value = request
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .change_context(Error::FetchSource)
+            .attach_printable_lazy(|| format!("Fetching {}", self.url))?
+            .json()
+            .await
+            .change_context(Error::FetchSource)
+            .attach_printable_lazy(|| format!("Reading JSON from {}", self.url))?;
+
+        let elements = response
+            .pointer(&self.items)
+            .and_then(|items| items.as_array())
+            .ok_or(Error::FetchSource)
+            .attach_printable_lazy(|| format!("No array at {:?} in the response", self.items))?;
+
+        let fetched_at = chrono::Utc::now();
+        let items = elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| {
+                element_item(&tera, element, fetched_at)
+                    .attach_printable_lazy(|| format!("Building item {i}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items
+            .into_iter()
+            .unique_by(|item| item.id.clone())
+            .collect())
+    }
+}
+
+/// Render the templates for one element of the item array.
+fn element_item(
+    tera: &tera::Tera,
+    element: &serde_json::Value,
+    fetched_at: chrono::DateTime<chrono::Utc>,
+) -> Result<AppItem, Report<Error>> {
+    let mut context = tera::Context::new();
+    context.insert("item", element);
+
+    let render = |name: &str| -> Result<Option<String>, Report<Error>> {
+        if tera.get_template(name).is_err() {
+            return Ok(None);
+        }
+        let value = tera
+            .render(name, &context)
+            .change_context(Error::FetchSource)
+            .attach_printable_lazy(|| format!("Rendering the {name} template"))?;
+        let value = value.trim();
+        Ok((!value.is_empty()).then(|| value.to_string()))
+    };
+
+    let id = render("id")?
+        .ok_or(Error::FetchSource)
+        .attach_printable("The id template rendered an empty string")?;
+
+    Ok(AppItem {
+        id,
+        state_key: None,
+        data: AppItemData {
+            title: render("title")?.unwrap_or_default(),
+            subtitle: render("subtitle")?,
+            detail: render("detail")?,
+            url: render("url")?,
+            icon: None,
+            data: None,
+        },
+        persistent: false,
+        notify: Vec::new(),
+        updated: fetched_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::HeaderMap, routing::get, Json, Router};
+    use serde_json::json;
+
+    use super::*;
+    use crate::tests::serve_stub;
+
+    async fn weather_server() -> String {
+        serve_stub(Router::new().route(
+            "/forecast",
+            get(|headers: HeaderMap| async move {
+                if !headers.get("x-api-key").is_some_and(|v| v == "secret") {
+                    return Err(axum::http::StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(json!({
+                    "location": "Springfield",
+                    "data": {
+                        "days": [
+                            { "date": "2024-01-01", "high": 12, "summary": "Sunny" },
+                            { "date": "2024-01-02", "high": 9, "summary": null },
+                        ]
+                    }
+                })))
+            }),
+        ))
+        .await
+    }
+
+    fn source(base: &str) -> JsonSource {
+        JsonSource {
+            url: format!("{base}/forecast"),
+            headers: BTreeMap::from([("x-api-key".to_string(), "secret".to_string())]),
+            items: "/data/days".to_string(),
+            fields: JsonFieldTemplates {
+                id: "{{ item.date }}".to_string(),
+                title: "{{ item.date }}: {{ item.high }}°".to_string(),
+                subtitle: None,
+                detail: Some("{{ item.summary }}".to_string()),
+                url: Some("https://weather.example.com/{{ item.date }}".to_string()),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn map_fields() {
+        let base = weather_server().await;
+        let items = source(&base).fetch(&reqwest::Client::new()).await.unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].id, "2024-01-01");
+        assert_eq!(items[0].data.title, "2024-01-01: 12°");
+        assert_eq!(items[0].data.subtitle, None);
+        assert_eq!(items[0].data.detail.as_deref(), Some("Sunny"));
+        assert_eq!(
+            items[0].data.url.as_deref(),
+            Some("https://weather.example.com/2024-01-01")
+        );
+        // Templates that render nothing, such as for a null value, leave the field empty.
+        assert_eq!(items[1].data.detail, None);
+    }
+
+    #[tokio::test]
+    async fn missing_header_fails() {
+        let base = weather_server().await;
+        let mut source = source(&base);
+        source.headers.clear();
+        let err = source.fetch(&reqwest::Client::new()).await.unwrap_err();
+        assert!(matches!(err.current_context(), Error::FetchSource));
+    }
+
+    #[tokio::test]
+    async fn items_must_be_an_array() {
+        let base = weather_server().await;
+        let mut source = source(&base);
+        source.items = "/location".to_string();
+        source
+            .fetch(&reqwest::Client::new())
+            .await
+            .expect_err("items should not be found");
+    }
+
+    #[test]
+    fn validation() {
+        let mut source = source("http://localhost");
+        source.items = "data".to_string();
+        source.fields.title = "{{ item.date".to_string();
+        source
+            .headers
+            .insert("bad header".to_string(), "value".to_string());
+
+        let mut issues = Vec::new();
+        source.validate(&mut issues);
+        let paths = issues.iter().map(|i| i.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "/builtin_source/headers/bad header",
+                "/builtin_source/items",
+                "/builtin_source/fields/title",
+            ]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Sources built into the platform, which publish an app's items without running an app
+//! executable.
+
+mod command;
+mod feed;
+mod json;
+
+use error_stack::{Report, ResultExt};
+use glance_app::AppItem;
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    command::{CommandFormat, CommandItemId, CommandSource},
+    feed::FeedSource,
+    json::JsonSource,
+};
+use crate::{
+    error::Error,
+    validation::{AppDataValidationError, ValidationIssue},
+};
+
+/// The task queue job which fetches the items for an app with a built-in source
+pub const BUILTIN_SOURCE_JOB: &str = "builtin-source";
+
+/// How often to poll a source by default, in seconds
+pub const DEFAULT_INTERVAL: u32 = 15 * 60;
+
+/// The shortest allowed poll interval, in seconds
+const MIN_INTERVAL: u32 = 60;
+
+/// A built-in source and how often to poll it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BuiltinSource {
+    /// How often to poll the source, in seconds
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// What to poll
+    #[serde(flatten)]
+    pub kind: BuiltinSourceKind,
+}
+
+fn default_interval() -> u32 {
+    DEFAULT_INTERVAL
+}
+
+/// The types of built-in sources
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuiltinSourceKind {
+    /// Entries from RSS or Atom feeds
+    Feed(FeedSource),
+    /// Elements of an array in a JSON response
+    Json(JsonSource),
+    /// The output of a shell command, which runs on the app's schedules instead of being polled
+    Command(CommandSource),
+}
+
+/// The payload of a [BUILTIN_SOURCE_JOB]. The source itself is read from the database when the
+/// job runs, so that it is always up to date.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuiltinSourceJobData {
+    pub app_id: String,
+}
+
+/// The ID of the recurring job that polls an app's built-in source
+pub fn builtin_source_job_id(app_id: &str) -> String {
+    format!("{app_id}:source")
+}
+
+impl BuiltinSource {
+    /// The path recorded for apps that use this source, since they have no executable
+    pub fn app_path(&self) -> &'static str {
+        match self.kind {
+            BuiltinSourceKind::Feed(_) => "builtin:feed",
+            BuiltinSourceKind::Json(_) => "builtin:json",
+            BuiltinSourceKind::Command(_) => "builtin:command",
+        }
+    }
+
+    /// How often to poll the source, or `None` for sources that run on the app's schedules
+    /// instead.
+    pub fn poll_interval(&self) -> Option<std::time::Duration> {
+        match self.kind {
+            BuiltinSourceKind::Command(_) => None,
+            _ => Some(std::time::Duration::from_secs(self.interval as u64)),
+        }
+    }
+
+    /// Check that the source is usable.
+    pub fn validate(&self) -> Result<(), Report<AppDataValidationError>> {
+        let mut issues = Vec::new();
+        if self.poll_interval().is_some() && self.interval < MIN_INTERVAL {
+            issues.push(ValidationIssue::new(
+                "/builtin_source/interval",
+                format!("The interval must be at least {MIN_INTERVAL} seconds"),
+            ));
+        }
+
+        match &self.kind {
+            BuiltinSourceKind::Feed(feed) => feed.validate(&mut issues),
+            BuiltinSourceKind::Json(json) => json.validate(&mut issues),
+            BuiltinSourceKind::Command(command) => command.validate(&mut issues),
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(Report::new(AppDataValidationError { issues }))
+        }
+    }
+
+    /// Fetch the current items from the source.
+    pub async fn fetch(&self, client: &reqwest::Client) -> Result<Vec<AppItem>, Report<Error>> {
+        match &self.kind {
+            BuiltinSourceKind::Feed(feed) => feed.fetch(client).await,
+            BuiltinSourceKind::Json(json) => json.fetch(client).await,
+            BuiltinSourceKind::Command(_) => Err(Report::new(Error::FetchSource))
+                .attach_printable("Command sources run on the app's schedules"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_json_source() {
+        let source: BuiltinSource = serde_json::from_value(json!({
+            "type": "json",
+            "interval": 300,
+            "url": "https://api.example.com/forecast",
+            "items": "/days",
+            "fields": {
+                "id": "{{ item.date }}",
+                "title": "{{ item.summary }}",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(source.interval, 300);
+        assert_eq!(source.app_path(), "builtin:json");
+        let BuiltinSourceKind::Json(json) = &source.kind else {
+            panic!("expected a JSON source, got {source:?}");
+        };
+        assert!(json.headers.is_empty());
+        assert_eq!(json.fields.url, None);
+        source.validate().unwrap();
+    }
+
+    #[test]
+    fn interval_too_short() {
+        let source: BuiltinSource = serde_json::from_value(json!({
+            "type": "feed",
+            "interval": 5,
+            "urls": ["https://example.com/feed.xml"],
+        }))
+        .unwrap();
+
+        let err = source.validate().unwrap_err();
+        let paths = err
+            .current_context()
+            .issues
+            .iter()
+            .map(|i| i.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["/builtin_source/interval"]);
+    }
+}
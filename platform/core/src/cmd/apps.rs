@@ -0,0 +1,404 @@
+use std::{
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{Args, Subcommand};
+use error_stack::{Report, ResultExt};
+use glance_app::{AppOutput, AppSchedule};
+
+use super::PlatformArgs;
+use crate::{
+    builtin_sources::{
+        BuiltinSource, BuiltinSourceKind, CommandFormat, CommandItemId, CommandSource, FeedSource,
+        DEFAULT_INTERVAL,
+    },
+    db::{AppInstallData, AppVisibility},
+    scheduled_task::{stderr_log_path, stdout_log_path},
+    Error, LOG_SUBDIR,
+};
+
+/// How often to check for new log output when following a log
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Manage the apps registered with the platform
+#[derive(Args, Debug)]
+pub struct AppsCommand {
+    #[clap(flatten)]
+    platform: PlatformArgs,
+
+    #[clap(subcommand)]
+    pub command: AppsSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AppsSubcommand {
+    /// List the apps and their status
+    List,
+    /// Install an app so that it runs on a schedule before it has published any data
+    Install(InstallCommand),
+    /// Enable a disabled app
+    Enable {
+        /// The app to enable
+        app_id: String,
+    },
+    /// Disable an app, pausing its schedule and hiding its items without deleting them
+    Disable {
+        /// The app to disable
+        app_id: String,
+    },
+    /// Remove an app, its items, and its data file
+    Uninstall {
+        /// The app to remove
+        app_id: String,
+    },
+    /// Remove an app and its items from the database. Unlike `uninstall`, this leaves the data
+    /// file in place, so the app will return the next time it publishes data.
+    Remove {
+        /// The app to remove
+        app_id: String,
+    },
+    /// List the recurring jobs in the task queue
+    Jobs {
+        /// Only show jobs for this app
+        app_id: Option<String>,
+    },
+    /// Queue a run of an app right away. The run happens on a server with scheduled tasks
+    /// enabled.
+    Run {
+        /// The app to run
+        app_id: String,
+    },
+    /// Show the output from an app's most recent scheduled run
+    Logs(LogsCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct LogsCommand {
+    /// The app whose logs to show
+    app_id: String,
+
+    /// Show standard error instead of standard output
+    #[clap(long)]
+    stderr: bool,
+
+    /// How many lines to show from the end of the log
+    #[clap(short = 'n', long, default_value_t = 20)]
+    lines: usize,
+
+    /// Keep printing new output as it is written
+    #[clap(short, long)]
+    follow: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct InstallCommand {
+    /// The ID for the app
+    id: String,
+
+    /// The path of the app's executable. Not needed for feed or shell command apps.
+    #[clap(required_unless_present_any = ["feed", "shell"])]
+    path: Option<String>,
+
+    /// The name of the app. Defaults to the ID.
+    #[clap(long)]
+    name: Option<String>,
+
+    /// A cron schedule on which to run the app. Can be given multiple times.
+    #[clap(long = "cron")]
+    cron: Vec<String>,
+
+    /// The command that runs the app, such as `--command bun run index.ts`. Everything after this
+    /// option is passed to the command, so it must come last. Defaults to running the path.
+    #[clap(long, num_args = 1.., allow_hyphen_values = true)]
+    command: Vec<String>,
+
+    /// The directory to run the app in. Defaults to the directory containing the path.
+    #[clap(long)]
+    working_dir: Option<String>,
+
+    /// The app prints its data to standard output instead of writing its data file
+    #[clap(long)]
+    stdout: bool,
+
+    /// How long to let each scheduled run take, in seconds
+    #[clap(long)]
+    timeout: Option<u32>,
+
+    /// The IANA timezone in which to evaluate the cron schedules. Defaults to UTC.
+    #[clap(long)]
+    timezone: Option<String>,
+
+    /// Instead of running an executable, publish the entries of this RSS or Atom feed. Can be
+    /// given multiple times.
+    #[clap(long = "feed")]
+    feed: Vec<String>,
+
+    /// How often to poll the feeds, in seconds
+    #[clap(long, default_value_t = DEFAULT_INTERVAL, requires = "feed")]
+    poll_interval: u32,
+
+    /// Instead of running an executable, run this shell command on the app's schedules and show
+    /// each line of its output as an item
+    #[clap(long, conflicts_with = "feed")]
+    shell: Option<String>,
+
+    /// The shell command prints a JSON array of item data instead of lines
+    #[clap(long, requires = "shell")]
+    json_output: bool,
+
+    /// How to give each item from the shell command an ID: "title", "position", or
+    /// "field:NAME" to use a field of each JSON item
+    #[clap(long, default_value = "title", requires = "shell")]
+    item_id: CommandItemId,
+}
+
+impl AppsCommand {
+    pub async fn handle(self) -> Result<(), Report<Error>> {
+        if let AppsSubcommand::Logs(cmd) = self.command {
+            return cmd.handle(&self.platform.base_dir()).await;
+        }
+
+        let db = self.platform.connect().await?;
+
+        match self.command {
+            AppsSubcommand::List => {
+                let apps = db.list_apps(&AppVisibility::All).await?;
+                if apps.is_empty() {
+                    println!("No apps");
+                }
+
+                for app in apps {
+                    let status = if app.enabled { "enabled" } else { "disabled" };
+                    println!("{:<24} {:<9} {}", app.id, status, app.name);
+                    if let Some(error) = app.error {
+                        println!("    error: {error}");
+                    }
+                    for issue in app.validation_errors.iter().flat_map(|e| e.iter()) {
+                        println!("      {issue}");
+                    }
+                }
+            }
+            AppsSubcommand::Install(cmd) => {
+                let install = AppInstallData {
+                    name: cmd.name.unwrap_or_else(|| cmd.id.clone()),
+                    id: cmd.id,
+                    path: cmd.path.unwrap_or_default(),
+                    command: (!cmd.command.is_empty()).then_some(cmd.command),
+                    working_dir: cmd.working_dir,
+                    output: if cmd.stdout {
+                        AppOutput::Stdout
+                    } else {
+                        AppOutput::File
+                    },
+                    schedule: cmd
+                        .cron
+                        .into_iter()
+                        .map(|cron| AppSchedule {
+                            cron,
+                            arguments: Vec::new(),
+                            timeout: cmd.timeout,
+                            timezone: cmd.timezone.clone(),
+                            command: None,
+                            working_dir: None,
+                        })
+                        .collect(),
+                    builtin_source: if let Some(command) = cmd.shell {
+                        Some(BuiltinSource {
+                            interval: cmd.poll_interval,
+                            kind: BuiltinSourceKind::Command(CommandSource {
+                                command,
+                                format: if cmd.json_output {
+                                    CommandFormat::Json
+                                } else {
+                                    CommandFormat::Lines
+                                },
+                                id: cmd.item_id,
+                            }),
+                        })
+                    } else {
+                        (!cmd.feed.is_empty()).then(|| BuiltinSource {
+                            interval: cmd.poll_interval,
+                            kind: BuiltinSourceKind::Feed(FeedSource::new(cmd.feed)),
+                        })
+                    },
+                };
+
+                let app = db.install_app(&install).await?;
+                println!("Installed {}", app.id);
+            }
+            AppsSubcommand::Enable { app_id } => {
+                set_enabled(&db, &app_id, true).await?;
+                println!("Enabled {app_id}");
+            }
+            AppsSubcommand::Disable { app_id } => {
+                set_enabled(&db, &app_id, false).await?;
+                println!("Disabled {app_id}");
+            }
+            AppsSubcommand::Uninstall { app_id } => {
+                if !db.uninstall_app(&app_id).await? {
+                    return Err(Report::new(Error::NotFound("App")));
+                }
+                println!("Uninstalled {app_id}");
+            }
+            AppsSubcommand::Remove { app_id } => {
+                db.remove_app(&app_id).await?;
+                println!("Removed {app_id}");
+            }
+            AppsSubcommand::Jobs { app_id } => {
+                let prefix = app_id.map(|id| format!("{id}:")).unwrap_or_default();
+                let jobs = db
+                    .task_queue
+                    .list_recurring_jobs_with_prefix(&prefix)
+                    .await
+                    .change_context(Error::TaskQueue)?;
+                if jobs.is_empty() {
+                    println!("No scheduled jobs");
+                }
+                for job in jobs {
+                    println!("{job}");
+                }
+            }
+            AppsSubcommand::Run { app_id } => {
+                if !db.trigger_app_run(&app_id).await? {
+                    return Err(Report::new(Error::NotFound("App")));
+                }
+                println!("Queued a run of {app_id}");
+            }
+            AppsSubcommand::Logs(_) => unreachable!("handled above"),
+        }
+
+        Ok(())
+    }
+}
+
+async fn set_enabled(db: &crate::db::Db, app_id: &str, enabled: bool) -> Result<(), Report<Error>> {
+    if db.set_app_enabled(app_id, enabled).await? {
+        Ok(())
+    } else {
+        Err(Report::new(Error::NotFound("App")))
+    }
+}
+
+impl LogsCommand {
+    async fn handle(self, base_dir: &Path) -> Result<(), Report<Error>> {
+        let log_dir = base_dir.join(LOG_SUBDIR);
+        let path = if self.stderr {
+            stderr_log_path(&log_dir, &self.app_id)
+        } else {
+            stdout_log_path(&log_dir, &self.app_id)
+        };
+
+        let contents = std::fs::read(&path)
+            .change_context(Error::ReadAppData)
+            .attach_printable_lazy(|| format!("Reading {}", path.display()))?;
+        let mut stdout = std::io::stdout();
+        stdout
+            .write_all(last_lines(&contents, self.lines))
+            .change_context(Error::Output)?;
+
+        if self.follow {
+            follow_log(&path, contents.len() as u64).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The slice of `contents` holding its last `count` lines
+fn last_lines(contents: &[u8], count: usize) -> &[u8] {
+    // Ignore a trailing newline so that it doesn't count as an empty last line.
+    let search = contents.strip_suffix(b"\n").unwrap_or(contents);
+    let start = search
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, &c)| c == b'\n')
+        .nth(count.saturating_sub(1))
+        .map(|(i, _)| i + 1)
+        .unwrap_or(0);
+
+    if count == 0 {
+        &contents[contents.len()..]
+    } else {
+        &contents[start..]
+    }
+}
+
+/// Print new data written to the log until the process is interrupted. Each run of an app
+/// replaces its log, so start again from the beginning if the file shrinks.
+async fn follow_log(path: &PathBuf, mut position: u64) -> Result<(), Report<Error>> {
+    let mut stdout = std::io::stdout();
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+
+        let Ok(mut file) = std::fs::File::open(path) else {
+            continue;
+        };
+        let len = file.metadata().change_context(Error::ReadAppData)?.len();
+        if len < position {
+            position = 0;
+        }
+        if len == position {
+            continue;
+        }
+
+        file.seek(SeekFrom::Start(position))
+            .change_context(Error::ReadAppData)?;
+        let mut buf = Vec::with_capacity((len - position) as usize);
+        file.read_to_end(&mut buf)
+            .change_context(Error::ReadAppData)?;
+        position += buf.len() as u64;
+
+        stdout.write_all(&buf).change_context(Error::Output)?;
+        stdout.flush().change_context(Error::Output)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{last_lines, AppsCommand, AppsSubcommand};
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        apps: AppsCommand,
+    }
+
+    #[test]
+    fn tail() {
+        let contents = b"a\nb\nc\n";
+        assert_eq!(last_lines(contents, 2), b"b\nc\n");
+        assert_eq!(last_lines(contents, 3), b"a\nb\nc\n");
+        assert_eq!(last_lines(contents, 10), b"a\nb\nc\n");
+        assert_eq!(last_lines(contents, 0), b"");
+        assert_eq!(last_lines(b"a\nb", 1), b"b");
+    }
+
+    #[test]
+    fn install_command_keeps_arguments() {
+        let cli = TestCli::try_parse_from([
+            "glance",
+            "install",
+            "app",
+            "index.ts",
+            "--command",
+            "bun",
+            "run",
+            "my app/index.ts",
+            "--verbose",
+        ])
+        .expect("parsing args");
+
+        let AppsSubcommand::Install(cmd) = cli.apps.command else {
+            panic!("expected install command");
+        };
+        assert_eq!(
+            cmd.command,
+            vec!["bun", "run", "my app/index.ts", "--verbose"]
+        );
+    }
+}
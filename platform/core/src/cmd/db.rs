@@ -1,15 +1,20 @@
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+};
+
 use clap::{Args, Subcommand};
 use error_stack::{Report, ResultExt};
 
-use crate::Error;
+use super::PlatformArgs;
+use crate::{archive::Archive, Error};
 
 mod bootstrap;
 
 #[derive(Args, Debug)]
 pub struct DbCommand {
-    /// The PostgreSQL database to connect to
-    #[clap(long = "db", env = "GLANCE_DATABASE_URL")]
-    database_url: String,
+    #[clap(flatten)]
+    platform: PlatformArgs,
 
     #[clap(subcommand)]
     pub command: DbSubcommand,
@@ -21,17 +26,90 @@ pub enum DbSubcommand {
     Bootstrap(bootstrap::BootstrapCommand),
     /// Update the database with the latest migrations
     Migrate,
+    /// Write the apps, items, notifications, and schedules to a JSON archive
+    Export(ExportCommand),
+    /// Restore a JSON archive created by `export`. Importing the same archive again is harmless.
+    Import(ImportCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct ExportCommand {
+    /// The file to write the archive to. Defaults to standard output.
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+
+    /// Include the event log in the archive
+    #[clap(long)]
+    events: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportCommand {
+    /// The archive to import, or `-` to read from standard input
+    input: PathBuf,
 }
 
 impl DbCommand {
     pub async fn handle(self) -> Result<(), Report<Error>> {
-        let pg_pool = sqlx::PgPool::connect(&self.database_url)
-            .await
-            .change_context(Error::Db)?;
-
         match self.command {
-            DbSubcommand::Bootstrap(cmd) => cmd.handle(pg_pool).await,
-            DbSubcommand::Migrate => crate::db::run_migrations(&pg_pool).await,
+            DbSubcommand::Bootstrap(cmd) => cmd.handle(self.platform.connect_pool().await?).await,
+            DbSubcommand::Migrate => {
+                crate::db::run_migrations(&self.platform.connect_pool().await?).await
+            }
+            DbSubcommand::Export(cmd) => cmd.handle(&self.platform).await,
+            DbSubcommand::Import(cmd) => cmd.handle(&self.platform).await,
+        }
+    }
+}
+
+impl ExportCommand {
+    async fn handle(self, platform: &PlatformArgs) -> Result<(), Report<Error>> {
+        let db = platform.connect().await?;
+        let archive = db.export_archive(self.events).await?;
+        let data = serde_json::to_vec_pretty(&archive).change_context(Error::InvalidArchive)?;
+
+        match &self.output {
+            Some(path) => std::fs::write(path, data)
+                .change_context(Error::InvalidArchive)
+                .attach_printable_lazy(|| format!("Writing {}", path.display()))?,
+            None => std::io::stdout()
+                .write_all(&data)
+                .change_context(Error::InvalidArchive)?,
         }
+
+        eprintln!(
+            "Exported {} apps, {} items, and {} notifications",
+            archive.apps.len(),
+            archive.items.len(),
+            archive.notifications.len()
+        );
+        Ok(())
+    }
+}
+
+impl ImportCommand {
+    async fn handle(self, platform: &PlatformArgs) -> Result<(), Report<Error>> {
+        let data = if self.input.as_os_str() == "-" {
+            let mut data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut data)
+                .change_context(Error::InvalidArchive)?;
+            data
+        } else {
+            std::fs::read(&self.input)
+                .change_context(Error::InvalidArchive)
+                .attach_printable_lazy(|| format!("Reading {}", self.input.display()))?
+        };
+
+        let archive: Archive =
+            serde_json::from_slice(&data).change_context(Error::InvalidArchive)?;
+
+        let db = platform.connect().await?;
+        let summary = db.import_archive(&archive).await?;
+        println!(
+            "Restored {} apps, {} items, {} notifications, and {} events",
+            summary.apps, summary.items, summary.notifications, summary.events
+        );
+        Ok(())
     }
 }
@@ -0,0 +1,83 @@
+use clap::{Args, Subcommand};
+use error_stack::Report;
+
+use super::PlatformArgs;
+use crate::Error;
+
+/// Inspect and manage the items that apps have published
+#[derive(Args, Debug)]
+pub struct ItemsCommand {
+    #[clap(flatten)]
+    platform: PlatformArgs,
+
+    #[clap(subcommand)]
+    pub command: ItemsSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ItemsSubcommand {
+    /// List an app's items, including dismissed items
+    List {
+        /// The app whose items to list
+        app_id: String,
+    },
+    /// Dismiss an item
+    Dismiss {
+        /// The app that owns the item
+        app_id: String,
+        /// The item to dismiss
+        item_id: String,
+    },
+    /// Restore a dismissed item
+    Undismiss {
+        /// The app that owns the item
+        app_id: String,
+        /// The item to restore
+        item_id: String,
+    },
+}
+
+impl ItemsCommand {
+    pub async fn handle(self) -> Result<(), Report<Error>> {
+        let db = self.platform.connect().await?;
+
+        match self.command {
+            ItemsSubcommand::List { app_id } => {
+                let items = db.read_app_items(&app_id).await?;
+                if items.is_empty() {
+                    println!("No items");
+                }
+
+                for item in items {
+                    let mut flags = Vec::new();
+                    if item.persistent {
+                        flags.push("persistent");
+                    }
+                    if item.dismissed {
+                        flags.push("dismissed");
+                    }
+
+                    println!(
+                        "{:<24} {:<20} {}",
+                        item.id,
+                        flags.join(","),
+                        item.data.title
+                    );
+                    if let Some(subtitle) = item.data.subtitle {
+                        println!("    {subtitle}");
+                    }
+                }
+            }
+            ItemsSubcommand::Dismiss { app_id, item_id } => {
+                db.set_item_dismissed(&app_id, &item_id, true).await?;
+                println!("Dismissed {item_id}");
+            }
+            ItemsSubcommand::Undismiss { app_id, item_id } => {
+                db.set_item_dismissed(&app_id, &item_id, false).await?;
+                println!("Restored {item_id}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,2 +1,64 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use error_stack::{Report, ResultExt};
+use glance_app::App;
+
+use crate::{
+    db::{Db, DbInner},
+    Error, WatchedRoot,
+};
+
+pub mod apps;
 pub mod db;
+pub mod items;
 pub mod util;
+
+/// Arguments for commands that work with the platform's data
+#[derive(Args, Debug)]
+pub struct PlatformArgs {
+    /// The PostgreSQL database to connect to
+    #[clap(long = "db", env = "GLANCE_DATABASE_URL")]
+    database_url: String,
+
+    /// The Glance data directory
+    #[clap(long, env = "GLANCE_BASE_DIR")]
+    base_dir: Option<PathBuf>,
+
+    /// The directories that the server watches for app data files, in the same form as for
+    /// `serve`, so that uninstalling an app removes its data file.
+    #[clap(
+        long = "watch-dir",
+        env = "GLANCE_WATCH_DIRS",
+        value_delimiter = ';',
+        value_parser = WatchedRoot::parse
+    )]
+    watched_roots: Vec<WatchedRoot>,
+}
+
+impl PlatformArgs {
+    /// The data directory, falling back to the default location
+    pub fn base_dir(&self) -> PathBuf {
+        self.base_dir.clone().unwrap_or_else(App::base_data_dir)
+    }
+
+    /// Connect to the database
+    pub async fn connect_pool(&self) -> Result<sqlx::PgPool, Report<Error>> {
+        sqlx::PgPool::connect(&self.database_url)
+            .await
+            .change_context(Error::Db)
+    }
+
+    /// Connect to the database and task queue
+    pub async fn connect(&self) -> Result<Db, Report<Error>> {
+        let pg_pool = self.connect_pool().await?;
+
+        let base_dir = self.base_dir();
+        std::fs::create_dir_all(&base_dir)
+            .change_context(Error::DbInit)
+            .attach_printable_lazy(|| format!("Creating {}", base_dir.display()))?;
+
+        let db = DbInner::new(pg_pool, &base_dir, self.watched_roots.clone()).await?;
+        Ok(std::sync::Arc::new(db))
+    }
+}
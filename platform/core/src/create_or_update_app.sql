@@ -3,13 +3,30 @@ INSERT INTO apps (
   name,
   path,
   ui,
-  version)
+  version,
+  settings_schema,
+  command,
+  working_dir,
+  output,
+  feedback_webhook,
+  data_source,
+  organization_id,
+  user_id)
 VALUES (
   $1,
   $2,
   $3,
   $4,
-  $5)
+  $5,
+  $6,
+  $7,
+  $8,
+  -- Apps that don't say how they return their data start out writing it to a file.
+  COALESCE($9, 'file'::app_output),
+  $10,
+  $11,
+  $12,
+  $13)
 ON CONFLICT (
   id)
   DO UPDATE SET
@@ -17,7 +34,25 @@ ON CONFLICT (
     path = EXCLUDED.path,
     ui = EXCLUDED.ui,
     version = EXCLUDED.version,
+    settings_schema = EXCLUDED.settings_schema,
+    command = EXCLUDED.command,
+    working_dir = EXCLUDED.working_dir,
+    output = COALESCE($9, apps.output),
+    feedback_webhook = EXCLUDED.feedback_webhook,
+    data_source = COALESCE(EXCLUDED.data_source, apps.data_source),
+    -- The user only matters along with the organization, so keep them together.
+    organization_id = COALESCE(EXCLUDED.organization_id, apps.organization_id),
+    user_id = CASE WHEN EXCLUDED.organization_id IS NULL THEN
+      apps.user_id
+    ELSE
+      EXCLUDED.user_id
+    END,
     updated_at = NOW(),
-    error = NULL
+    error = NULL,
+    validation_errors = NULL
   WHERE
-    EXCLUDED.version >= apps.version;
+    EXCLUDED.version >= apps.version
+  RETURNING
+    enabled,
+    output AS "output: AppOutput",
+    builtin_source AS "builtin_source: Json<BuiltinSource>";
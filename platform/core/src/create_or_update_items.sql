@@ -0,0 +1,30 @@
+INSERT INTO items (
+  id,
+  app_id,
+  data,
+  state_key,
+  persistent,
+  updated_at,
+  dismissed)
+SELECT
+  input.id,
+  $1,
+  input.data,
+  input.state_key,
+  input.persistent,
+  input.updated_at,
+  NOT input.resurface
+FROM
+  UNNEST($2::text[], $3::jsonb[], $4::text[], $5::boolean[], $6::timestamptz[],
+    $7::boolean[]) AS input (id, data, state_key, persistent, updated_at, resurface)
+ON CONFLICT (
+  app_id,
+  id)
+  DO UPDATE SET
+    data = EXCLUDED.data,
+    persistent = EXCLUDED.persistent,
+    state_key = EXCLUDED.state_key,
+    updated_at = NOW(),
+    -- EXCLUDED.dismissed is false when the item should resurface
+    dismissed = items.dismissed
+      AND EXCLUDED.dismissed
@@ -1,4 +1,8 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use effectum::Queue;
 use error_stack::{Report, ResultExt};
@@ -9,24 +13,37 @@ use filigree::{
         users::add_user_email_login,
     },
 };
-use glance_app::{AppData, AppItemData, Notification};
+use glance_app::{
+    AppData, AppItemData, AppOutput, AppSchedule, AppUiInfo, Feedback, FeedbackKind, Notification,
+    APP_SETTINGS_SUBDIR,
+};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgConnection, PgExecutor, PgPool};
-use tracing::instrument;
+use sqlx::{types::Json, PgConnection, PgExecutor, PgPool};
+use sqlx_transparent_json_decode::BoxedRawValue;
+use tracing::{event, instrument, Level};
 
 use crate::{
+    app_settings::{merge_settings, AppSettings},
+    auth::AuthInfo,
+    builtin_sources::{
+        builtin_source_job_id, BuiltinSource, BuiltinSourceJobData, BuiltinSourceKind,
+        BUILTIN_SOURCE_JOB,
+    },
     items::{AppInfo, AppItems, Item},
     models::{
         organization::OrganizationId,
         role::{self, Role, RoleId},
         user::{UserCreatePayload, UserId},
     },
-    scheduled_task::ScheduledJobData,
+    rejected_payload::RejectedPayload,
+    scheduled_task::{AppLaunch, FeedbackWebhookData, ScheduledJobData, FEEDBACK_WEBHOOK_JOB},
+    schedules::{upcoming_runs, Schedule, ScheduleId, TIMEZONE_SCHEDULES_JOB},
     users::{
         organization::create_new_organization, users::create_new_user_with_prehashed_password,
     },
-    Error,
+    validation::{self, ValidationIssue},
+    AppDataSource, AppOwner, Error, WatchedRoot,
 };
 
 /// Run the database migrations, if needed
@@ -139,11 +156,121 @@ async fn create_superuser_role(
     Ok(superuser_role_id)
 }
 
+/// The information needed to install an app through [DbInner::install_app]
+#[derive(Debug, Deserialize)]
+pub struct AppInstallData {
+    /// The ID for the app
+    pub id: String,
+    /// The name of the app
+    pub name: String,
+    /// The path of the app's executable. This can be omitted for apps with a built-in source.
+    #[serde(default)]
+    pub path: String,
+    /// The command that runs the app, if it's not run from `path` directly
+    #[serde(default)]
+    pub command: Option<Vec<String>>,
+    /// The directory to run the app in
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// How the app returns its data when it runs
+    #[serde(default)]
+    pub output: AppOutput,
+    /// When the platform should run the app
+    #[serde(default)]
+    pub schedule: Vec<AppSchedule>,
+    /// A built-in source that publishes the app's items instead of an executable
+    #[serde(default)]
+    pub builtin_source: Option<BuiltinSource>,
+}
+
+/// Build the task queue job that runs an app once.
+fn scheduled_job(
+    app_id: &str,
+    launch: &AppLaunch,
+    schedule_id: Option<ScheduleId>,
+    schedule: AppSchedule,
+) -> Result<effectum::Job, Report<Error>> {
+    let timeout = std::time::Duration::from_secs(schedule.timeout.unwrap_or(300) as u64);
+    let job = effectum::Job::builder("scheduled-app")
+        .json_payload(&ScheduledJobData {
+            app_id: app_id.to_string(),
+            command: launch.path.clone(),
+            app_command: launch.command.as_ref().map(|c| c.0.clone()),
+            working_dir: launch.working_dir.clone(),
+            output: launch.output,
+            builtin_source: launch.builtin_source.as_ref().map(|s| s.0.clone()),
+            schedule_id,
+            schedule,
+        })
+        .change_context(Error::TaskQueue)?
+        .timeout(timeout)
+        .build();
+    Ok(job)
+}
+
+/// Build the task queue job that polls an app's built-in source once.
+fn builtin_source_job(app_id: &str) -> Result<effectum::Job, Report<Error>> {
+    let job = effectum::Job::builder(BUILTIN_SOURCE_JOB)
+        .json_payload(&BuiltinSourceJobData {
+            app_id: app_id.to_string(),
+        })
+        .change_context(Error::TaskQueue)?
+        .timeout(std::time::Duration::from_secs(120))
+        .build();
+    Ok(job)
+}
+
+/// Which apps a read includes. Apps without an owner are visible to everyone.
+#[derive(Clone, Debug)]
+pub enum AppVisibility {
+    /// Every app, for the platform itself and the command line
+    All,
+    /// The apps that a viewer in an organization can see, which are those owned by the
+    /// organization as a whole or by the viewer. A viewer who isn't logged in only sees apps
+    /// without an owner.
+    Viewer(Option<(OrganizationId, UserId)>),
+}
+
+impl AppVisibility {
+    /// The apps visible to the sender of a request
+    pub fn for_auth(auth: Option<&AuthInfo>) -> Self {
+        Self::Viewer(auth.map(|auth| (auth.organization_id, auth.user_id)))
+    }
+
+    /// Whether every app is visible, and the viewer's organization and user
+    fn query_params(&self) -> (bool, Option<&uuid::Uuid>, Option<&uuid::Uuid>) {
+        match self {
+            Self::All => (true, None, None),
+            Self::Viewer(viewer) => (
+                false,
+                viewer.as_ref().map(|(org, _)| org.as_uuid()),
+                viewer.as_ref().map(|(_, user)| user.as_uuid()),
+            ),
+        }
+    }
+}
+
+/// The number of items that an app has
+#[derive(Debug)]
+pub struct ItemCounts {
+    pub app_id: String,
+    /// Items that have not been dismissed
+    pub active: i64,
+    pub dismissed: i64,
+}
+
+/// How many rejected app data payloads to keep for each app
+pub const REJECTED_PAYLOADS_TO_KEEP: i64 = 10;
+
 /// The database for the glance platform
 pub struct DbInner {
     /// The database connection pool
     pub pool: sqlx::PgPool,
     pub(crate) task_queue: Queue,
+    /// Where to write the organization-wide settings for each app
+    settings_dir: PathBuf,
+    /// The directories containing the app data files
+    pub(crate) watched_roots: Vec<WatchedRoot>,
 }
 
 impl std::fmt::Debug for DbInner {
@@ -169,16 +296,37 @@ pub enum EventType {
     RemoveApp,
     /// A scheduled app was executed
     ScheduledRun,
+    /// The viewer dismissed an item
+    DismissItem,
+    /// The viewer brought back a dismissed item
+    UndismissItem,
 }
 
 impl DbInner {
-    /// Create a new database connection and run migrations if needed.
-    pub async fn new(pool: PgPool, data_dir: &Path) -> Result<Self, Report<Error>> {
+    /// Create a new database connection and run migrations if needed. If `watched_roots` is
+    /// empty, the `data` subdirectory of `data_dir` holds the app data files.
+    pub async fn new(
+        pool: PgPool,
+        data_dir: &Path,
+        watched_roots: Vec<WatchedRoot>,
+    ) -> Result<Self, Report<Error>> {
+        let watched_roots = if watched_roots.is_empty() {
+            vec![WatchedRoot::default_for(data_dir)]
+        } else {
+            watched_roots
+        };
+        WatchedRoot::check_prefixes(&watched_roots)?;
+
         let task_queue = Queue::new(&data_dir.join("glance_tasks.db"))
             .await
             .change_context(Error::DbInit)?;
 
-        Ok(Self { pool, task_queue })
+        Ok(Self {
+            pool,
+            task_queue,
+            settings_dir: data_dir.join(APP_SETTINGS_SUBDIR),
+            watched_roots,
+        })
     }
 
     #[instrument(skip(self))]
@@ -204,11 +352,13 @@ impl DbInner {
         conn: impl PgExecutor<'_>,
         app_id: &str,
         error: Option<&str>,
+        validation_errors: Option<&[ValidationIssue]>,
     ) -> Result<(), Report<Error>> {
         sqlx::query!(
-            r##"UPDATE apps SET updated_at = now(), error = $2 WHERE id = $1"##,
+            r##"UPDATE apps SET updated_at = now(), error = $2, validation_errors = $3 WHERE id = $1"##,
             app_id,
-            error
+            error,
+            validation_errors.map(Json) as _
         )
         .execute(conn)
         .await
@@ -216,15 +366,270 @@ impl DbInner {
         Ok(())
     }
 
+    /// Save an app data update that failed, keeping only the most recent
+    /// [REJECTED_PAYLOADS_TO_KEEP] payloads for the app.
+    #[instrument(skip(self, contents))]
+    pub async fn add_rejected_payload(
+        &self,
+        app_id: &str,
+        source: AppDataSource,
+        merge_items: bool,
+        contents: &str,
+        error: &str,
+        validation_errors: Option<&[ValidationIssue]>,
+    ) -> Result<(), Report<Error>> {
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+
+        sqlx::query_file!(
+            "src/add_rejected_payload.sql",
+            app_id,
+            source as _,
+            merge_items,
+            contents,
+            error,
+            validation_errors.map(Json) as _
+        )
+        .execute(&mut *tx)
+        .await
+        .change_context(Error::Db)?;
+
+        sqlx::query!(
+            "DELETE FROM rejected_payloads
+            WHERE app_id = $1 AND id NOT IN (
+                SELECT id FROM rejected_payloads WHERE app_id = $1 ORDER BY id DESC LIMIT $2
+            )",
+            app_id,
+            REJECTED_PAYLOADS_TO_KEEP
+        )
+        .execute(&mut *tx)
+        .await
+        .change_context(Error::Db)?;
+
+        tx.commit().await.change_context(Error::Db)?;
+        Ok(())
+    }
+
+    /// List the saved rejected payloads for an app, newest first.
+    #[instrument(skip(self))]
+    pub async fn get_rejected_payloads(
+        &self,
+        app_id: &str,
+    ) -> Result<Vec<RejectedPayload>, Report<Error>> {
+        sqlx::query_file_as!(RejectedPayload, "src/get_rejected_payloads.sql", app_id)
+            .fetch_all(&self.pool)
+            .await
+            .change_context(Error::Db)
+    }
+
+    /// Read a single rejected payload.
+    #[instrument(skip(self))]
+    pub async fn get_rejected_payload(
+        &self,
+        app_id: &str,
+        id: i64,
+    ) -> Result<Option<RejectedPayload>, Report<Error>> {
+        sqlx::query_file_as!(RejectedPayload, "src/get_rejected_payload.sql", app_id, id)
+            .fetch_optional(&self.pool)
+            .await
+            .change_context(Error::Db)
+    }
+
+    /// Remove a rejected payload from the store.
+    #[instrument(skip(self))]
+    pub async fn remove_rejected_payload(
+        &self,
+        app_id: &str,
+        id: i64,
+    ) -> Result<(), Report<Error>> {
+        sqlx::query!(
+            "DELETE FROM rejected_payloads WHERE app_id = $1 AND id = $2",
+            app_id,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+        Ok(())
+    }
+
+    /// Read an app's settings schema and the values that apply to a user. Returns `None` if the
+    /// app does not exist.
+    #[instrument(skip(self))]
+    pub async fn get_app_settings(
+        &self,
+        app_id: &str,
+        organization_id: OrganizationId,
+        user_id: UserId,
+    ) -> Result<Option<AppSettings>, Report<Error>> {
+        let row = sqlx::query_file!(
+            "src/get_app_settings.sql",
+            app_id,
+            organization_id.as_uuid(),
+            user_id.as_uuid()
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        Ok(row.map(|row| AppSettings {
+            values: merge_settings(row.organization.as_ref(), row.user.as_ref()),
+            schema: row.schema,
+            organization: row.organization,
+            user: row.user,
+        }))
+    }
+
+    /// Save settings for an app, for a single user or for the whole organization if `user_id`
+    /// is `None`. When organization-wide settings change, the app's settings file is rewritten
+    /// with the settings from [DbInner::get_platform_app_settings].
+    #[instrument(skip(self))]
+    pub async fn set_app_settings(
+        &self,
+        app_id: &str,
+        organization_id: OrganizationId,
+        user_id: Option<UserId>,
+        value: &serde_json::Value,
+    ) -> Result<(), Report<Error>> {
+        sqlx::query_file!(
+            "src/set_app_settings.sql",
+            app_id,
+            organization_id.as_uuid(),
+            user_id.as_ref().map(|id| id.as_uuid()),
+            value
+        )
+        .execute(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        if user_id.is_none() {
+            let settings = self.get_platform_app_settings(app_id).await?;
+            self.write_settings_file(app_id, settings.as_ref())
+                .await
+                .change_context(Error::Db)
+                .attach_printable("Writing app settings file")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the app's settings file, or remove it if the app has no settings that the platform
+    /// can use.
+    async fn write_settings_file(
+        &self,
+        app_id: &str,
+        value: Option<&serde_json::Value>,
+    ) -> Result<(), std::io::Error> {
+        let path = self.settings_dir.join(format!("{app_id}.json"));
+        let Some(value) = value else {
+            return match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        };
+
+        tokio::fs::create_dir_all(&self.settings_dir).await?;
+        // Write and rename so that the app never sees a partially-written file.
+        let tmp_path = self.settings_dir.join(format!("{app_id}.json.tmp"));
+        tokio::fs::write(&tmp_path, serde_json::to_vec_pretty(value)?).await?;
+        tokio::fs::rename(&tmp_path, path).await
+    }
+
+    /// The settings to use when the platform runs an app itself. These are the
+    /// organization-wide settings of the organization that owns the app. For an app without an
+    /// owner, the organization-wide settings are only used when a single organization has set
+    /// them, so that one organization's settings are never used on behalf of another.
+    #[instrument(skip(self))]
+    pub async fn get_platform_app_settings(
+        &self,
+        app_id: &str,
+    ) -> Result<Option<serde_json::Value>, Report<Error>> {
+        let mut rows = sqlx::query!(
+            r##"SELECT app_settings.organization_id, app_settings.value,
+                apps.organization_id AS owner_organization_id
+            FROM app_settings
+            JOIN apps ON apps.id = app_settings.app_id
+            WHERE app_settings.app_id = $1 AND app_settings.user_id IS NULL"##,
+            app_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        let owner = rows.first().and_then(|row| row.owner_organization_id);
+        let settings = match owner {
+            Some(owner) => rows
+                .into_iter()
+                .find(|row| row.organization_id == owner)
+                .map(|row| row.value),
+            None if rows.len() > 1 => {
+                event!(
+                    Level::WARN,
+                    %app_id,
+                    "Ignoring settings from several organizations for an app without an owner"
+                );
+                None
+            }
+            None => rows.pop().map(|row| row.value),
+        };
+
+        Ok(settings)
+    }
+
     /// List all the known apps
     #[instrument(skip(self))]
     pub async fn get_apps(&self, app_ids: &[String]) -> Result<Vec<AppInfo>, Report<Error>> {
-        sqlx::query_file_as!(AppInfo, "src/get_apps.sql", app_ids)
+        self.get_visible_apps(app_ids, &AppVisibility::All).await
+    }
+
+    /// Read the apps in `app_ids` that are visible.
+    #[instrument(skip(self))]
+    pub async fn get_visible_apps(
+        &self,
+        app_ids: &[String],
+        visibility: &AppVisibility,
+    ) -> Result<Vec<AppInfo>, Report<Error>> {
+        let (all, organization_id, user_id) = visibility.query_params();
+        sqlx::query_file_as!(
+            AppInfo,
+            "src/get_apps.sql",
+            app_ids,
+            all,
+            organization_id,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)
+    }
+
+    /// List every visible app, including disabled apps
+    #[instrument(skip(self))]
+    pub async fn list_apps(
+        &self,
+        visibility: &AppVisibility,
+    ) -> Result<Vec<AppInfo>, Report<Error>> {
+        let (all, organization_id, user_id) = visibility.query_params();
+        sqlx::query_file_as!(AppInfo, "src/list_apps.sql", all, organization_id, user_id)
             .fetch_all(&self.pool)
             .await
             .change_context(Error::Db)
     }
 
+    /// List the apps whose most recent data came from `source`.
+    #[instrument(skip(self))]
+    pub async fn get_app_ids_by_source(
+        &self,
+        source: AppDataSource,
+    ) -> Result<Vec<String>, Report<Error>> {
+        sqlx::query_scalar!(
+            "SELECT id FROM apps WHERE data_source = $1 ORDER BY id",
+            source as _
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)
+    }
+
     /// Remove an app and all its associated items.
     #[instrument(skip(self))]
     pub async fn remove_app(&self, app_id: &str) -> Result<(), Report<Error>> {
@@ -235,7 +640,7 @@ impl DbInner {
 
         let scheduled = self
             .task_queue
-            .list_recurring_jobs_with_prefix(app_id)
+            .list_recurring_jobs_with_prefix(&format!("{app_id}:"))
             .await
             .change_context(Error::TaskQueue)?;
         for job in scheduled {
@@ -249,7 +654,8 @@ impl DbInner {
         Ok(())
     }
 
-    /// Update the dismissed state of an item.
+    /// Update the dismissed state of an item. A change is recorded as feedback for the app, and
+    /// sent to the app's feedback webhook if it has one.
     #[instrument(skip(self))]
     pub async fn set_item_dismissed(
         &self,
@@ -257,46 +663,266 @@ impl DbInner {
         item_id: &str,
         dismissed: bool,
     ) -> Result<(), Report<Error>> {
-        sqlx::query!(
-            "UPDATE items SET dismissed = $3 WHERE app_id = $1 AND id = $2",
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+
+        let webhook = sqlx::query_scalar!(
+            r##"UPDATE items SET dismissed = $3
+            FROM apps
+            WHERE items.app_id = $1 AND items.id = $2 AND items.dismissed <> $3
+                AND apps.id = items.app_id
+            RETURNING apps.feedback_webhook"##,
             app_id,
             item_id,
             dismissed
         )
-        .execute(&self.pool)
+        .fetch_optional(&mut *tx)
+        .await
+        .change_context(Error::Db)?;
+
+        // The item doesn't exist or was already in this state, so there's no feedback to give.
+        let Some(webhook) = webhook else {
+            return Ok(());
+        };
+
+        let kind = if dismissed {
+            FeedbackKind::Dismiss
+        } else {
+            FeedbackKind::Undismiss
+        };
+        let feedback = sqlx::query_file_as!(
+            Feedback,
+            "src/add_feedback_event.sql",
+            kind as _,
+            app_id,
+            item_id
+        )
+        .fetch_one(&mut *tx)
         .await
         .change_context(Error::Db)?;
+
+        tx.commit().await.change_context(Error::Db)?;
+
+        if let Some(url) = webhook {
+            let job = effectum::Job::builder(FEEDBACK_WEBHOOK_JOB)
+                .json_payload(&FeedbackWebhookData {
+                    app_id: app_id.to_string(),
+                    url,
+                    feedback,
+                })
+                .change_context(Error::TaskQueue)?
+                .build();
+            self.task_queue
+                .add_job(job)
+                .await
+                .change_context(Error::TaskQueue)?;
+        }
+
         Ok(())
     }
 
-    /// Update an app, or create it if it doesn't exist.
+    /// Read the feedback on an app's items, oldest first. If `since_id` is set, only feedback
+    /// from events with a greater ID is returned.
+    #[instrument(skip(self))]
+    pub async fn get_app_feedback(
+        &self,
+        app_id: &str,
+        since_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Feedback>, Report<Error>> {
+        sqlx::query_file_as!(
+            Feedback,
+            "src/get_app_feedback.sql",
+            app_id,
+            since_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)
+    }
+
+    /// Update an app, or create it if it doesn't exist. If the submitted version is older than
+    /// the stored version, nothing is changed. `source` records where the data came from, and
+    /// `None` keeps the existing source.
     #[instrument(skip(self))]
     pub async fn create_or_update_app(
         &self,
-        tx: impl PgExecutor<'_>,
+        tx: &mut PgConnection,
         app_id: &str,
         app: &AppData,
+        source: Option<AppDataSource>,
+        owner: Option<&AppOwner>,
     ) -> Result<(), Report<Error>> {
-        sqlx::query_file!(
+        let row = sqlx::query_file!(
             "src/create_or_update_app.sql",
             app_id,
             app.name,
             app.path,
             sqlx::types::Json(&app.ui) as _,
-            app.version as i32
+            app.version as i32,
+            app.settings_schema.as_ref().map(sqlx::types::Json) as _,
+            app.command.as_ref().map(sqlx::types::Json) as _,
+            app.working_dir.as_deref(),
+            app.output as _,
+            app.feedback_webhook.as_deref(),
+            source as _,
+            owner.map(|owner| owner.organization_id.as_uuid()),
+            owner.and_then(|owner| owner.user_id.as_ref().map(|id| id.as_uuid()))
         )
-        .execute(tx)
+        .fetch_optional(&mut *tx)
         .await
         .change_context(Error::Db)?;
 
+        let Some(row) = row else {
+            return Ok(());
+        };
+
+        let schedules = self
+            .write_app_schedules(&mut *tx, app_id, &app.schedule)
+            .await?;
+
+        // Disabled apps keep their schedules but have no jobs until they are enabled again.
+        let launch = AppLaunch {
+            path: app.path.clone(),
+            command: app.command.clone().map(Json),
+            working_dir: app.working_dir.clone(),
+            output: row.output,
+            builtin_source: row.builtin_source,
+        };
+        self.sync_scheduled_jobs(app_id, row.enabled.then_some(&launch), &schedules)
+            .await
+    }
+
+    /// Replace an app's schedules. Schedules that match an existing schedule's cron spec and
+    /// arguments keep their IDs.
+    #[instrument(skip(self, tx))]
+    pub(crate) async fn write_app_schedules(
+        &self,
+        tx: &mut PgConnection,
+        app_id: &str,
+        schedule: &[AppSchedule],
+    ) -> Result<Vec<Schedule>, Report<Error>> {
+        let schedule = schedule
+            .iter()
+            .unique_by(|s| (&s.cron, &s.arguments))
+            .collect::<Vec<_>>();
+
+        let ids = schedule
+            .iter()
+            .map(|_| *ScheduleId::new().as_uuid())
+            .collect::<Vec<_>>();
+        let crons = schedule.iter().map(|s| s.cron.clone()).collect::<Vec<_>>();
+        let arguments = schedule
+            .iter()
+            .map(|s| serde_json::json!(s.arguments))
+            .collect::<Vec<_>>();
+        let timeouts = schedule
+            .iter()
+            .map(|s| s.timeout.map(|t| t as i32))
+            .collect::<Vec<_>>();
+        let timezones = schedule
+            .iter()
+            .map(|s| s.timezone.clone())
+            .collect::<Vec<_>>();
+        let now = chrono::Utc::now();
+        let next_runs = schedule
+            .iter()
+            .map(|s| {
+                let timezone = s.timezone.as_deref()?;
+                upcoming_runs(&s.cron, Some(timezone), now, 1).pop()
+            })
+            .collect::<Vec<_>>();
+        let commands = schedule
+            .iter()
+            .map(|s| s.command.clone().map(Json))
+            .collect::<Vec<_>>();
+        let working_dirs = schedule
+            .iter()
+            .map(|s| s.working_dir.clone())
+            .collect::<Vec<_>>();
+
+        sqlx::query_file!(
+            "src/write_app_schedules.sql",
+            app_id,
+            &ids,
+            &crons,
+            &arguments,
+            &timeouts,
+            &timezones as _,
+            &next_runs as _,
+            &commands as _,
+            &working_dirs as _
+        )
+        .execute(&mut *tx)
+        .await
+        .change_context(Error::Db)?;
+
+        self.get_app_schedules(&mut *tx, app_id).await
+    }
+
+    /// Read an app's schedules, in the order that the app declared them.
+    #[instrument(skip(self, db))]
+    pub async fn get_app_schedules(
+        &self,
+        db: impl PgExecutor<'_>,
+        app_id: &str,
+    ) -> Result<Vec<Schedule>, Report<Error>> {
+        sqlx::query_file_as!(Schedule, "src/get_app_schedules.sql", app_id)
+            .fetch_all(db)
+            .await
+            .change_context(Error::Db)
+    }
+
+    /// Make the app's recurring jobs in the task queue match `schedules` and its built-in source.
+    /// Schedules with a timezone don't get a recurring job, since they are queued by
+    /// [DbInner::queue_due_schedules] instead. Passing `None` for the launch removes all of the
+    /// app's jobs, as for a disabled app.
+    pub(crate) async fn sync_scheduled_jobs(
+        &self,
+        app_id: &str,
+        launch: Option<&AppLaunch>,
+        schedules: &[Schedule],
+    ) -> Result<(), Report<Error>> {
         let mut existing_jobs = self
             .task_queue
             .list_recurring_jobs_with_prefix(&format!("{app_id}:"))
             .await
             .change_context(Error::TaskQueue)?;
 
-        for schedule in &app.schedule {
-            let job_id = format!("{app_id}:{}", schedule.cron);
+        let (launch, schedules) = match launch {
+            Some(launch) => (launch, schedules),
+            None => {
+                for to_remove in existing_jobs {
+                    self.task_queue
+                        .delete_recurring_job(to_remove)
+                        .await
+                        .change_context(Error::TaskQueue)?;
+                }
+                return Ok(());
+            }
+        };
+
+        let poll_interval = launch
+            .builtin_source
+            .as_ref()
+            .and_then(|source| source.poll_interval());
+        if let Some(interval) = poll_interval {
+            let job_id = builtin_source_job_id(app_id);
+            existing_jobs.retain(|existing| existing != &job_id);
+            // Poll right away so that a new source doesn't wait a full interval for its items.
+            self.task_queue
+                .upsert_recurring_job(
+                    job_id,
+                    effectum::RecurringJobSchedule::RepeatEvery { interval },
+                    builtin_source_job(app_id)?,
+                    true,
+                )
+                .await
+                .change_context(Error::TaskQueue)?;
+        }
+
+        for schedule in schedules.iter().filter(|s| s.timezone.is_none()) {
+            let job_id = schedule.job_id();
             existing_jobs.retain(|existing| existing != &job_id);
             self.task_queue
                 .upsert_recurring_job(
@@ -304,17 +930,7 @@ impl DbInner {
                     effectum::RecurringJobSchedule::Cron {
                         spec: schedule.cron.clone(),
                     },
-                    effectum::Job::builder("scheduled-app")
-                        .json_payload(&ScheduledJobData {
-                            app_id: app_id.to_string(),
-                            command: app.path.clone(),
-                            schedule: schedule.clone(),
-                        })
-                        .change_context(Error::TaskQueue)?
-                        .timeout(std::time::Duration::from_secs(
-                            schedule.timeout.unwrap_or(300) as u64,
-                        ))
-                        .build(),
+                    scheduled_job(app_id, launch, Some(schedule.id), schedule.app_schedule())?,
                     false,
                 )
                 .await
@@ -333,6 +949,412 @@ impl DbInner {
         Ok(())
     }
 
+    /// Bring the task queue in line with the schedules in the database. This restores jobs that
+    /// are missing from the queue, such as when the task queue file is lost, and removes jobs
+    /// for schedules, apps, and disabled apps that no longer exist.
+    #[instrument(skip(self))]
+    pub async fn reconcile_scheduled_jobs(&self) -> Result<(), Report<Error>> {
+        let apps = sqlx::query!(
+            r##"SELECT id, path, command AS "command: Json<Vec<String>>", working_dir,
+                output AS "output: AppOutput",
+                builtin_source AS "builtin_source: Json<BuiltinSource>"
+            FROM apps WHERE enabled"##
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        self.task_queue
+            .upsert_recurring_job(
+                TIMEZONE_SCHEDULES_JOB.to_string(),
+                effectum::RecurringJobSchedule::Cron {
+                    spec: "0 * * * * *".to_string(),
+                },
+                effectum::Job::builder(TIMEZONE_SCHEDULES_JOB).build(),
+                false,
+            )
+            .await
+            .change_context(Error::TaskQueue)?;
+
+        let mut expected_jobs = HashSet::from([TIMEZONE_SCHEDULES_JOB.to_string()]);
+        for app in apps {
+            let schedules = self.get_app_schedules(&self.pool, &app.id).await?;
+            expected_jobs.extend(
+                schedules
+                    .iter()
+                    .filter(|s| s.timezone.is_none())
+                    .map(|s| s.job_id()),
+            );
+            if app
+                .builtin_source
+                .as_ref()
+                .is_some_and(|source| source.poll_interval().is_some())
+            {
+                expected_jobs.insert(builtin_source_job_id(&app.id));
+            }
+            let launch = AppLaunch {
+                path: app.path,
+                command: app.command,
+                working_dir: app.working_dir,
+                output: app.output,
+                builtin_source: app.builtin_source,
+            };
+            self.sync_scheduled_jobs(&app.id, Some(&launch), &schedules)
+                .await?;
+        }
+
+        let existing_jobs = self
+            .task_queue
+            .list_recurring_jobs_with_prefix("")
+            .await
+            .change_context(Error::TaskQueue)?;
+        for job in existing_jobs {
+            if !expected_jobs.contains(&job) {
+                self.task_queue
+                    .delete_recurring_job(job)
+                    .await
+                    .change_context(Error::TaskQueue)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Queue runs for the schedules with a timezone that are due, and work out when each of
+    /// them should run next.
+    #[instrument(skip(self))]
+    pub async fn queue_due_schedules(&self) -> Result<(), Report<Error>> {
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+        let due = sqlx::query_file!("src/get_due_schedules.sql")
+            .fetch_all(&mut *tx)
+            .await
+            .change_context(Error::Db)?;
+
+        let now = chrono::Utc::now();
+        for row in due {
+            let schedule = Schedule {
+                id: row.id,
+                app_id: row.app_id,
+                cron: row.cron,
+                arguments: row.arguments,
+                timeout: row.timeout,
+                timezone: row.timezone,
+                last_run_at: row.last_run_at,
+                command: row.command,
+                working_dir: row.working_dir,
+            };
+            let launch = AppLaunch {
+                path: row.path,
+                command: row.app_command,
+                working_dir: row.app_working_dir,
+                output: row.output,
+                builtin_source: row.builtin_source,
+            };
+
+            // A schedule without a next run time has not been checked yet, so it isn't due.
+            if row.next_run_at.is_some() {
+                let job = scheduled_job(
+                    &schedule.app_id,
+                    &launch,
+                    Some(schedule.id),
+                    schedule.app_schedule(),
+                )?;
+                self.task_queue
+                    .add_job(job)
+                    .await
+                    .change_context(Error::TaskQueue)?;
+            }
+
+            let next_run = schedule.upcoming(now, 1).pop();
+            sqlx::query!(
+                "UPDATE schedules SET next_run_at = $2 WHERE id = $1",
+                schedule.id.as_uuid(),
+                next_run
+            )
+            .execute(&mut *tx)
+            .await
+            .change_context(Error::Db)?;
+        }
+
+        tx.commit().await.change_context(Error::Db)?;
+        Ok(())
+    }
+
+    /// Record that a schedule started a run of its app.
+    #[instrument(skip(self))]
+    pub async fn record_schedule_run(&self, id: &ScheduleId) -> Result<(), Report<Error>> {
+        sqlx::query!(
+            "UPDATE schedules SET last_run_at = now() WHERE id = $1",
+            id.as_uuid()
+        )
+        .execute(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+        Ok(())
+    }
+
+    /// Register a new app without waiting for it to publish any data.
+    #[instrument(skip(self))]
+    pub async fn install_app(&self, install: &AppInstallData) -> Result<AppInfo, Report<Error>> {
+        let mut path = install.path.clone();
+        let mut command = install.command.clone();
+        let mut output = install.output;
+        if let Some(source) = &install.builtin_source {
+            source.validate().change_context(Error::InvalidAppData)?;
+            if path.is_empty() {
+                path = source.app_path().to_string();
+            }
+
+            // Command sources run like any other app that prints its data.
+            if let BuiltinSourceKind::Command(source) = &source.kind {
+                command = Some(source.app_command());
+                output = AppOutput::Stdout;
+            }
+        }
+
+        let app = AppData {
+            name: install.name.clone(),
+            path,
+            command,
+            working_dir: install.working_dir.clone(),
+            output: Some(output),
+            feedback_webhook: None,
+            items: Vec::new(),
+            schedule: install.schedule.clone(),
+            ui: None,
+            version: 0,
+            settings_schema: None,
+        };
+        validation::validate_app_data(&app).change_context(Error::InvalidAppData)?;
+
+        let mut tx = self.pool.begin().await.change_context(Error::Db)?;
+        // Claim the ID first, so that a concurrent install of the same app waits on this row and
+        // then fails, instead of overwriting it.
+        let inserted = sqlx::query_scalar!(
+            "INSERT INTO apps (id, name, path) VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO NOTHING
+            RETURNING id",
+            &install.id,
+            &app.name,
+            &app.path
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .change_context(Error::Db)?
+        .is_some();
+        if !inserted {
+            return Err(Report::new(Error::AppAlreadyExists));
+        }
+
+        self.create_or_update_app(&mut *tx, &install.id, &app, None, None)
+            .await?;
+
+        if let Some(source) = &install.builtin_source {
+            sqlx::query!(
+                "UPDATE apps SET builtin_source = $2 WHERE id = $1",
+                &install.id,
+                Json(source) as _
+            )
+            .execute(&mut *tx)
+            .await
+            .change_context(Error::Db)?;
+        }
+        tx.commit().await.change_context(Error::Db)?;
+
+        if install.builtin_source.is_some() {
+            // Add the job that polls the source, now that the app has one.
+            let launch = self.get_app_launch(&install.id).await?;
+            let schedules = self.get_app_schedules(&self.pool, &install.id).await?;
+            self.sync_scheduled_jobs(&install.id, launch.as_ref(), &schedules)
+                .await?;
+        }
+
+        self.get_apps(&[install.id.clone()])
+            .await?
+            .pop()
+            .ok_or(Error::NotFound("App"))
+            .attach_printable("App missing after install")
+    }
+
+    /// Queue a run of an app right away, outside its schedule. The run uses the arguments and
+    /// timeout of the app's first schedule, if it has one. Apps with a polled built-in source
+    /// poll the source instead. Returns false if the app does not exist.
+    #[instrument(skip(self))]
+    pub async fn trigger_app_run(&self, app_id: &str) -> Result<bool, Report<Error>> {
+        let Some(launch) = self.get_app_launch(app_id).await? else {
+            return Ok(false);
+        };
+
+        let polled = launch
+            .builtin_source
+            .as_ref()
+            .is_some_and(|source| source.poll_interval().is_some());
+        if polled {
+            self.task_queue
+                .add_job(builtin_source_job(app_id)?)
+                .await
+                .change_context(Error::TaskQueue)?;
+            return Ok(true);
+        }
+
+        let schedule = self
+            .get_app_schedules(&self.pool, app_id)
+            .await?
+            .first()
+            .map(|s| s.app_schedule())
+            .unwrap_or(AppSchedule {
+                cron: String::new(),
+                arguments: Vec::new(),
+                timeout: None,
+                timezone: None,
+                command: None,
+                working_dir: None,
+            });
+
+        let job = scheduled_job(app_id, &launch, None, schedule)?;
+        self.task_queue
+            .add_job(job)
+            .await
+            .change_context(Error::TaskQueue)?;
+        Ok(true)
+    }
+
+    /// Enable or disable an app. Disabling an app removes its scheduled jobs and hides its
+    /// items, without deleting anything. Returns false if the app does not exist.
+    #[instrument(skip(self))]
+    pub async fn set_app_enabled(
+        &self,
+        app_id: &str,
+        enabled: bool,
+    ) -> Result<bool, Report<Error>> {
+        let launch = sqlx::query_as!(
+            AppLaunch,
+            r##"UPDATE apps SET enabled = $2, updated_at = now() WHERE id = $1
+            RETURNING path, command AS "command: Json<Vec<String>>", working_dir,
+                output AS "output: AppOutput",
+                builtin_source AS "builtin_source: Json<BuiltinSource>""##,
+            app_id,
+            enabled
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        let Some(launch) = launch else {
+            return Ok(false);
+        };
+
+        let schedules = self.get_app_schedules(&self.pool, app_id).await?;
+        self.sync_scheduled_jobs(app_id, enabled.then_some(&launch), &schedules)
+            .await?;
+        Ok(true)
+    }
+
+    /// Read an app's built-in source, along with the app data that the source's items are
+    /// published with. The app data carries the app's stored configuration so that publishing
+    /// the items leaves it unchanged. Returns `None` if the app does not exist, is disabled, or
+    /// has no built-in source.
+    #[instrument(skip(self))]
+    pub(crate) async fn get_builtin_source_app(
+        &self,
+        app_id: &str,
+    ) -> Result<Option<(AppData, BuiltinSource)>, Report<Error>> {
+        let row = sqlx::query!(
+            r##"SELECT name, path, version, command AS "command: Json<Vec<String>>", working_dir,
+                output AS "output: AppOutput", feedback_webhook,
+                ui AS "ui: Json<Option<AppUiInfo>>", settings_schema AS "settings_schema: BoxedRawValue",
+                builtin_source AS "builtin_source!: Json<BuiltinSource>"
+            FROM apps
+            WHERE id = $1 AND enabled AND builtin_source IS NOT NULL"##,
+            app_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .change_context(Error::Db)?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let schedule = self
+            .get_app_schedules(&self.pool, app_id)
+            .await?
+            .iter()
+            .map(|s| s.app_schedule())
+            .collect();
+        let app = AppData {
+            name: row.name,
+            path: row.path,
+            command: row.command.map(|c| c.0),
+            working_dir: row.working_dir,
+            output: Some(row.output),
+            feedback_webhook: row.feedback_webhook,
+            items: Vec::new(),
+            schedule,
+            ui: row.ui.0,
+            version: row.version as u32,
+            settings_schema: row.settings_schema,
+        };
+        Ok(Some((app, row.builtin_source.0)))
+    }
+
+    /// Count the active and dismissed items for each app.
+    #[instrument(skip(self))]
+    pub async fn item_counts(&self) -> Result<Vec<ItemCounts>, Report<Error>> {
+        sqlx::query_as!(
+            ItemCounts,
+            r##"SELECT app_id,
+                COUNT(*) FILTER (WHERE NOT dismissed) AS "active!",
+                COUNT(*) FILTER (WHERE dismissed) AS "dismissed!"
+            FROM items
+            GROUP BY app_id"##
+        )
+        .fetch_all(&self.pool)
+        .await
+        .change_context(Error::Db)
+    }
+
+    /// Read how to start an app.
+    pub(crate) async fn get_app_launch(
+        &self,
+        app_id: &str,
+    ) -> Result<Option<AppLaunch>, Report<Error>> {
+        sqlx::query_file_as!(AppLaunch, "src/get_app_launch.sql", app_id)
+            .fetch_optional(&self.pool)
+            .await
+            .change_context(Error::Db)
+    }
+
+    /// Remove an app, its items, and its data file. Returns false if the app does not exist.
+    #[instrument(skip(self))]
+    pub async fn uninstall_app(&self, app_id: &str) -> Result<bool, Report<Error>> {
+        let exists = !self.get_apps(&[app_id.to_string()]).await?.is_empty();
+        if !exists {
+            return Ok(false);
+        }
+
+        // Remove the data file first so that the app is not added again the next time the app
+        // data directories are scanned.
+        let data_file = self
+            .watched_roots
+            .iter()
+            .find_map(|root| root.path_for_app(app_id));
+        if let Some(data_file) = data_file {
+            match tokio::fs::remove_file(&data_file).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e)
+                        .change_context(Error::ReadAppData)
+                        .attach_printable_lazy(|| format!("Removing {}", data_file.display()))
+                }
+            }
+        }
+
+        self.remove_app(app_id).await?;
+        Ok(true)
+    }
+
     /// Update an item, or update it if an item with the same ID already exists.
     #[instrument(skip(self))]
     pub async fn create_or_update_item(
@@ -357,6 +1379,50 @@ impl DbInner {
         Ok(())
     }
 
+    /// Create or update many items for an app in a single statement. Each item is paired with
+    /// its `resurface` flag, as in [DbInner::create_or_update_item].
+    #[instrument(skip(self, items), fields(count = items.len()))]
+    pub async fn create_or_update_items(
+        &self,
+        tx: impl PgExecutor<'_>,
+        app_id: &str,
+        items: &[(Item, bool)],
+    ) -> Result<(), Report<Error>> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let mut ids = Vec::with_capacity(items.len());
+        let mut data = Vec::with_capacity(items.len());
+        let mut state_keys = Vec::with_capacity(items.len());
+        let mut persistent = Vec::with_capacity(items.len());
+        let mut updated_at = Vec::with_capacity(items.len());
+        let mut resurface = Vec::with_capacity(items.len());
+        for (item, item_resurface) in items {
+            ids.push(item.id.as_str());
+            data.push(Json(&item.data));
+            state_keys.push(item.state_key.as_deref());
+            persistent.push(item.persistent);
+            updated_at.push(item.updated_at);
+            resurface.push(*item_resurface);
+        }
+
+        sqlx::query_file!(
+            "src/create_or_update_items.sql",
+            app_id,
+            &ids as _,
+            &data as _,
+            &state_keys as _,
+            &persistent,
+            &updated_at,
+            &resurface
+        )
+        .execute(tx)
+        .await
+        .change_context(Error::Db)?;
+        Ok(())
+    }
+
     /// Remove the items with ids that do not match the passed list
     #[instrument(skip(self))]
     pub async fn remove_unfound_items(
@@ -387,9 +1453,12 @@ impl DbInner {
         Ok(items)
     }
 
-    /// Read all the non-dismissed items for all apps from the database
+    /// Read all the non-dismissed items for the visible apps from the database
     #[instrument(skip(self))]
-    pub async fn read_active_items(&self) -> Result<Vec<AppItems>, Report<Error>> {
+    pub async fn read_active_items(
+        &self,
+        visibility: &AppVisibility,
+    ) -> Result<Vec<AppItems>, Report<Error>> {
         let mut items = sqlx::query_file_as!(Item, "src/get_active_items.sql")
             .fetch_all(&self.pool)
             .await
@@ -402,7 +1471,8 @@ impl DbInner {
             .into_grouping_map_by(|item| item.app_id.to_string())
             .collect::<Vec<_>>();
         let app_ids = items_by_app_id.keys().cloned().collect::<Vec<_>>();
-        let apps = self.get_apps(&app_ids).await?;
+        // Items from apps that aren't visible are dropped along with their apps.
+        let apps = self.get_visible_apps(&app_ids, visibility).await?;
 
         let apps_with_items = apps
             .into_iter()
@@ -1,5 +1,5 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
 };
 use error_stack::Report;
@@ -11,6 +11,9 @@ use filigree::{
 };
 use thiserror::Error;
 
+/// How many seconds a client should wait before retrying when the change queue is unavailable
+const CHANGE_QUEUE_RETRY_AFTER: &str = "1";
+
 /// The top-level error type from the platform
 #[derive(Debug, Error)]
 pub enum Error {
@@ -29,15 +32,33 @@ pub enum Error {
     /// Failure deserializing an app data file
     #[error("Failed to read app data")]
     ReadAppData,
+    /// Submitted app data failed validation
+    #[error("Invalid app data")]
+    InvalidAppData,
+    /// Tried to install an app with an ID that is already in use
+    #[error("An app with this ID already exists")]
+    AppAlreadyExists,
+    /// Submitted app settings failed validation
+    #[error("Invalid app settings")]
+    InvalidSettings,
+    /// An export archive could not be read or restored
+    #[error("Invalid archive")]
+    InvalidArchive,
     /// Failed to start the HTTP server
     #[error("Failed to start server")]
     ServerStart,
     /// Failure while shutting down
     #[error("Encountered error while shutting down")]
     Shutdown,
+    /// The change pipeline can not accept more app updates right now
+    #[error("App updates are unavailable: {0}")]
+    ChangeQueueUnavailable(&'static str),
     /// Error running a scheduled task
     #[error("Error running scheduled task")]
     ScheduledTask,
+    /// A built-in source could not be fetched
+    #[error("Failed to fetch from source")]
+    FetchSource,
     /// The requested item was not found
     #[error("{0} not found")]
     NotFound(&'static str),
@@ -66,6 +87,9 @@ pub enum Error {
     InvalidHostHeader,
     #[error("Type Export Error")]
     TypeExport,
+    /// Failed to write command output to the terminal
+    #[error("Failed to write output")]
+    Output,
 }
 
 impl From<Report<Error>> for Error {
@@ -140,11 +164,17 @@ impl HttpError for Error {
             Error::DbInit => FilErrorKind::DatabaseInit.as_str(),
             Error::Db => FilErrorKind::Database.as_str(),
             Error::ReadAppData => ErrorKind::ReadAppData.as_str(),
+            Error::InvalidAppData => ErrorKind::InvalidAppData.as_str(),
+            Error::InvalidSettings => ErrorKind::InvalidSettings.as_str(),
+            Error::AppAlreadyExists => ErrorKind::AppAlreadyExists.as_str(),
+            Error::InvalidArchive => ErrorKind::InvalidArchive.as_str(),
             Error::TaskQueue => ErrorKind::TaskQueue.as_str(),
             Error::ServerStart => FilErrorKind::ServerStart.as_str(),
             Error::NotFound(_) => FilErrorKind::NotFound.as_str(),
             Error::Shutdown => FilErrorKind::Shutdown.as_str(),
             Error::ScheduledTask => ErrorKind::ScheduledTask.as_str(),
+            Error::FetchSource => ErrorKind::FetchSource.as_str(),
+            Error::ChangeQueueUnavailable(_) => ErrorKind::ChangeQueueUnavailable.as_str(),
             Error::Filter => ErrorKind::Filter.as_str(),
             Error::AuthError(e) => e.error_kind(),
             Error::AuthSubsystem => ErrorKind::AuthSubsystem.as_str(),
@@ -156,6 +186,7 @@ impl HttpError for Error {
             // These aren't ever returned, we just need some value to fill out the match
             Error::Config => "config",
             Error::TypeExport => "cli",
+            Error::Output => "cli",
         }
     }
 
@@ -187,10 +218,16 @@ impl HttpError for Error {
             Error::Db => StatusCode::INTERNAL_SERVER_ERROR,
             Error::TaskQueue => StatusCode::INTERNAL_SERVER_ERROR,
             Error::ReadAppData => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::InvalidAppData => StatusCode::BAD_REQUEST,
+            Error::InvalidSettings => StatusCode::BAD_REQUEST,
+            Error::AppAlreadyExists => StatusCode::CONFLICT,
+            Error::InvalidArchive => StatusCode::BAD_REQUEST,
             Error::ServerStart => StatusCode::INTERNAL_SERVER_ERROR,
             Error::NotFound(_) => StatusCode::NOT_FOUND,
             Error::Shutdown => StatusCode::INTERNAL_SERVER_ERROR,
             Error::ScheduledTask => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::FetchSource => StatusCode::BAD_GATEWAY,
+            Error::ChangeQueueUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
             Error::Filter => StatusCode::BAD_REQUEST,
             Error::AuthSubsystem => StatusCode::INTERNAL_SERVER_ERROR,
             Error::MissingPermission(_) => StatusCode::FORBIDDEN,
@@ -200,6 +237,7 @@ impl HttpError for Error {
             Error::Storage => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Config => StatusCode::INTERNAL_SERVER_ERROR,
             Error::TypeExport => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::Output => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
@@ -213,14 +251,28 @@ impl HttpError for Error {
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        self.to_response()
+        let retry = matches!(self, Error::ChangeQueueUnavailable(_));
+        let mut response = self.to_response();
+        if retry {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from_static(CHANGE_QUEUE_RETRY_AFTER),
+            );
+        }
+        response
     }
 }
 
 pub enum ErrorKind {
     ReadAppData,
+    InvalidAppData,
+    InvalidSettings,
+    AppAlreadyExists,
+    InvalidArchive,
     TaskQueue,
     ScheduledTask,
+    FetchSource,
+    ChangeQueueUnavailable,
     Filter,
     AuthSubsystem,
     Login,
@@ -231,8 +283,14 @@ impl ErrorKind {
     pub fn as_str(&self) -> &'static str {
         match self {
             ErrorKind::ReadAppData => "read_app_data",
+            ErrorKind::InvalidAppData => "invalid_app_data",
+            ErrorKind::InvalidSettings => "invalid_settings",
+            ErrorKind::AppAlreadyExists => "app_already_exists",
+            ErrorKind::InvalidArchive => "invalid_archive",
             ErrorKind::TaskQueue => "task_queue",
             ErrorKind::ScheduledTask => "scheduled_task",
+            ErrorKind::FetchSource => "fetch_source",
+            ErrorKind::ChangeQueueUnavailable => "change_queue_unavailable",
             ErrorKind::Filter => "invalid_filter",
             ErrorKind::AuthSubsystem => "auth",
             ErrorKind::MissingId => "missing_id",
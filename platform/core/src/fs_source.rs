@@ -1,145 +1,638 @@
 use std::{
-    path::{Path, PathBuf},
+    collections::HashSet,
+    path::Path,
     thread::JoinHandle,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use error_stack::{Report, ResultExt};
-use glance_app::APP_DATA_SUBDIR;
-use notify_debouncer_mini::{new_debouncer, notify::RecursiveMode, DebounceEventResult};
+use notify::{RecursiveMode, Watcher};
 use thiserror::Error;
+use tracing::{event, Level};
 
-use crate::{AppFileContents, AppFileInput};
+use crate::{
+    db::Db,
+    platform_health::{WorkerGuard, WorkerStatus},
+    AppDataSource, AppFileContents, AppFileInput, AppOwner, WatchedRoot,
+};
 
 #[derive(Debug, Error)]
 #[error("Watcher error")]
 pub struct WatcherError {}
 
-/// Monitor a directory for updated .json files
+/// How long to collect changes before reading the changed files, so that a file which is written
+/// in several steps is only read once.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How long to wait before the first restart of a failed watcher
+const MIN_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// The longest to wait between restarts of a watcher that keeps failing
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+/// A watcher that runs this long before failing starts over at the shortest restart delay.
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+/// Monitor a set of directories for updated .json files. The watcher is restarted if it fails,
+/// and the directories are scanned again each time it starts so that changes made while it was
+/// down are not missed.
 pub struct FsSource {
     // Hold a reference to keep things open, until this is dropped
     shutdown_tx: flume::Sender<()>,
-    watcher_thread: JoinHandle<Result<(), Report<WatcherError>>>,
+    supervisor_thread: JoinHandle<()>,
+    status: WorkerStatus,
 }
 
 impl FsSource {
     pub fn new(
-        base_dir: PathBuf,
+        roots: Vec<WatchedRoot>,
         change_tx: flume::Sender<AppFileInput>,
     ) -> Result<Self, std::io::Error> {
         let (shutdown_tx, shutdown_rx) = flume::bounded(0);
-        let data_dir = base_dir.join(APP_DATA_SUBDIR);
 
-        std::fs::create_dir_all(&data_dir)?;
-        let watcher_thread =
-            std::thread::spawn(move || Self::watcher(shutdown_rx, data_dir, change_tx));
+        let roots = roots
+            .into_iter()
+            .map(|mut root| {
+                std::fs::create_dir_all(&root.dir)?;
+                // Match the form of the paths in the watcher's events.
+                root.dir = root.dir.canonicalize()?;
+                Ok(root)
+            })
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+        let (status, guard) = WorkerStatus::start();
+        let supervisor_thread =
+            std::thread::spawn(move || Self::supervise(shutdown_rx, roots, change_tx, guard));
 
         Ok(Self {
             shutdown_tx,
-            watcher_thread,
+            supervisor_thread,
+            status,
         })
     }
 
+    /// Whether the watcher is running. This is false while a failed watcher waits to restart.
+    pub fn status(&self) -> WorkerStatus {
+        self.status.clone()
+    }
+
     /// Close the FsSource and wait for the watcher thread to close.
     /// It is also ok to just drop the FsSource if you don't care about waiting for it to shutdown.
     pub fn close(self) {
         let Self {
             shutdown_tx,
-            watcher_thread,
+            supervisor_thread,
+            ..
         } = self;
         drop(shutdown_tx);
-        match watcher_thread.join() {
-            Ok(Ok(())) => {}
-            Ok(Err(e)) => eprintln!("{:?}", e),
-            Err(e) => eprintln!("Watcher thread panicked {:?}", e),
-        };
+        if let Err(e) = supervisor_thread.join() {
+            event!(Level::ERROR, error = ?e, "App data watcher thread panicked");
+        }
     }
 
-    fn watcher(
+    /// Run the watcher until shutdown, restarting it with an increasing delay each time it fails.
+    fn supervise(
         shutdown_rx: flume::Receiver<()>,
-        data_dir: PathBuf,
+        roots: Vec<WatchedRoot>,
         change_tx: flume::Sender<AppFileInput>,
+        guard: WorkerGuard,
+    ) {
+        let mut delay = MIN_RESTART_DELAY;
+        loop {
+            let started = Instant::now();
+            let Err(e) = Self::watch(&shutdown_rx, &roots, &change_tx, &guard) else {
+                return;
+            };
+
+            guard.set_running(false);
+            if started.elapsed() >= STABLE_RUN {
+                delay = MIN_RESTART_DELAY;
+            }
+
+            metrics::counter!("glance_fs_watcher_restarts_total").increment(1);
+            event!(Level::ERROR, error = ?e, retry_in = ?delay,
+                "App data watcher failed, restarting");
+            // Anything other than a timeout means that the FsSource is shutting down.
+            if !matches!(
+                shutdown_rx.recv_timeout(delay),
+                Err(flume::RecvTimeoutError::Timeout)
+            ) {
+                return;
+            }
+
+            delay = next_restart_delay(delay);
+        }
+    }
+
+    /// Watch the directories, sending the contents of changed files to `change_tx`. This returns
+    /// Ok when `shutdown_rx` closes, and an error if the watcher fails.
+    fn watch(
+        shutdown_rx: &flume::Receiver<()>,
+        roots: &[WatchedRoot],
+        change_tx: &flume::Sender<AppFileInput>,
+        guard: &WorkerGuard,
     ) -> Result<(), Report<WatcherError>> {
-        // Start the watcher
-        let watcher_change_tx = change_tx.clone();
-        let mut watcher = new_debouncer(
-            Duration::from_millis(500),
-            move |res: DebounceEventResult| match res {
-                Ok(events) => {
-                    for event in events {
-                        println!("updated file: {}", event.path.display());
-                        match read_file(&event.path)
-                            .attach_printable_lazy(|| event.path.clone().display().to_string())
-                        {
-                            Ok(Some(result)) => {
-                                watcher_change_tx.send(result).ok();
-                            }
-                            Ok(None) => {}
-                            Err(e) => eprintln!("{:?}", e),
-                        };
-                    }
-                }
-                Err(e) => eprintln!("watcher error: {:?}", e),
-            },
-        )
+        let (event_tx, event_rx) = flume::unbounded();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            event_tx.send(res).ok();
+        })
         .change_context(WatcherError {})?;
 
-        watcher
-            .watcher()
-            .watch(&data_dir, RecursiveMode::NonRecursive)
-            .change_context(WatcherError {})?;
-
-        // Scan the directory and send change events for all the data that was already there.
-        let dir = std::fs::read_dir(&data_dir)
-            .change_context(WatcherError {})?
-            .filter_map(Result::ok);
-        for entry in dir {
-            let path = entry.path();
-            match read_file(&path) {
-                Ok(Some(result)) => {
-                    change_tx.send(result).ok();
+        for root in roots {
+            // The directory may have been removed while the watcher was down.
+            std::fs::create_dir_all(&root.dir)
+                .change_context(WatcherError {})
+                .attach_printable_lazy(|| format!("Creating {}", root.dir.display()))?;
+            watcher
+                .watch(&root.dir, RecursiveMode::NonRecursive)
+                .change_context(WatcherError {})
+                .attach_printable_lazy(|| format!("Watching {}", root.dir.display()))?;
+        }
+
+        // Send the data that was already there, or that changed while the watcher was down.
+        for root in roots {
+            scan_dir(root, change_tx)?;
+        }
+        guard.set_running(true);
+
+        enum Next {
+            Shutdown,
+            Event(notify::Result<notify::Event>),
+            Flush,
+        }
+
+        let mut pending = HashSet::new();
+        let mut flush_at = None;
+        loop {
+            let selector = flume::Selector::new()
+                .recv(shutdown_rx, |_| Next::Shutdown)
+                .recv(&event_rx, |res| match res {
+                    Ok(res) => Next::Event(res),
+                    // The watcher holds the sender, so this can't happen while it's alive.
+                    Err(_) => Next::Shutdown,
+                });
+            let next = match flush_at {
+                Some(deadline) => selector.wait_deadline(deadline).unwrap_or(Next::Flush),
+                None => selector.wait(),
+            };
+
+            match next {
+                Next::Shutdown => return Ok(()),
+                Next::Event(Ok(event)) if event.need_rescan() => {
+                    // The watcher missed some events, such as when its queue overflowed, so
+                    // read everything again.
+                    event!(Level::WARN, "Rescanning app data directories");
+                    pending.clear();
+                    flush_at = None;
+                    for root in roots {
+                        scan_dir(root, change_tx)?;
+                    }
+                }
+                Next::Event(Ok(event)) => {
+                    pending.extend(event.paths);
+                    flush_at.get_or_insert_with(|| Instant::now() + DEBOUNCE);
+                }
+                Next::Event(Err(e)) => {
+                    return Err(e).change_context(WatcherError {});
+                }
+                Next::Flush => {
+                    for path in pending.drain() {
+                        let root = roots
+                            .iter()
+                            .find(|root| path.parent() == Some(root.dir.as_path()));
+                        if let Some(root) = root {
+                            send_file(root, &path, change_tx);
+                        }
+                    }
+                    flush_at = None;
                 }
-                Ok(None) => {}
-                Err(e) => eprintln!("reading {}: {:?}", path.display(), e),
             }
         }
+    }
+}
+
+/// Remove the apps whose data file was deleted while the platform was not running, since the
+/// watcher never saw those deletions. Apps whose latest data came from somewhere other than a
+/// watched directory are left alone, as are apps whose ID doesn't match any watched directory,
+/// and apps from a directory that is missing or can't be read, such as a drive that isn't
+/// mounted yet.
+pub async fn remove_deleted_apps(
+    db: &Db,
+    roots: &[WatchedRoot],
+) -> Result<(), Report<crate::Error>> {
+    let mut readable = Vec::with_capacity(roots.len());
+    for root in roots {
+        let result = tokio::fs::read_dir(&root.dir).await;
+        if let Err(e) = &result {
+            event!(Level::WARN, dir = %root.dir.display(), error = %e,
+                "Not checking for deleted apps in an unreadable directory");
+        }
+        readable.push(result.is_ok());
+    }
+
+    for app_id in db.get_app_ids_by_source(AppDataSource::File).await? {
+        // The prefixes don't overlap, so at most one root can hold the app's file.
+        let Some((path, readable)) = roots
+            .iter()
+            .zip(&readable)
+            .find_map(|(root, readable)| root.path_for_app(&app_id).map(|path| (path, *readable)))
+        else {
+            continue;
+        };
+
+        // If the file can't be checked, assume that it's still there.
+        if !readable || tokio::fs::try_exists(&path).await.unwrap_or(true) {
+            continue;
+        }
 
-        // Block on the shutdown channel while the watcher runs in the background.
-        shutdown_rx.recv().ok();
-        Ok(())
+        event!(Level::INFO, %app_id, "Removing app whose data file was deleted");
+        db.remove_app(&app_id).await?;
     }
+
+    Ok(())
+}
+
+impl WatchedRoot {
+    /// The app that a file in this directory belongs to, or `None` if it's not an app data file.
+    fn app_id_for_path(&self, path: &Path) -> Option<String> {
+        if path.extension().unwrap_or_default() != "json" {
+            return None;
+        }
+
+        path.file_stem()
+            .map(|stem| format!("{}{}", self.app_id_prefix, stem.to_string_lossy()))
+    }
+}
+
+fn next_restart_delay(delay: Duration) -> Duration {
+    (delay * 2).min(MAX_RESTART_DELAY)
 }
 
-fn read_file(path: &Path) -> Result<Option<AppFileInput>, std::io::Error> {
-    if path.extension().unwrap_or_default() != "json" {
-        return Ok(None);
+/// Send every app data file in the directory.
+fn scan_dir(
+    root: &WatchedRoot,
+    change_tx: &flume::Sender<AppFileInput>,
+) -> Result<(), Report<WatcherError>> {
+    let dir = std::fs::read_dir(&root.dir)
+        .change_context(WatcherError {})
+        .attach_printable_lazy(|| format!("Scanning {}", root.dir.display()))?
+        .filter_map(Result::ok);
+    for entry in dir {
+        send_file(root, &entry.path(), change_tx);
     }
+    Ok(())
+}
 
-    let Some(app_id) = path.file_stem() else {
-        return Ok(None);
+/// Read an app data file and send its contents to the change handler. Errors are logged, since
+/// the file will be read again the next time it changes.
+fn send_file(root: &WatchedRoot, path: &Path, change_tx: &flume::Sender<AppFileInput>) {
+    let Some(app_id) = root.app_id_for_path(path) else {
+        return;
     };
 
-    let app_id = app_id.to_string_lossy().to_string();
+    match read_file(path, app_id.clone(), root.owner.clone()) {
+        Ok(input) => {
+            event!(Level::INFO, %app_id, path = %path.display(), "App data file changed");
+            change_tx.send(input).ok();
+        }
+        Err(e) => {
+            event!(Level::ERROR, %app_id, path = %path.display(), error = ?e,
+                "Failed to read app data file");
+        }
+    }
+}
 
+fn read_file(
+    path: &Path,
+    app_id: String,
+    owner: Option<AppOwner>,
+) -> Result<AppFileInput, std::io::Error> {
     let data = match std::fs::read_to_string(path) {
         Ok(file) => file,
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
                 // The file was deleted.
-                return Ok(Some(AppFileInput {
+                return Ok(AppFileInput {
                     app_id,
                     contents: AppFileContents::Empty,
                     merge_items: false,
-                }));
+                    source: AppDataSource::File,
+                    owner,
+                });
             } else {
                 return Err(e);
             }
         }
     };
 
-    Ok(Some(AppFileInput {
+    Ok(AppFileInput {
         app_id,
         contents: AppFileContents::Raw(data),
         merge_items: false,
-    }))
+        source: AppDataSource::File,
+        owner,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use glance_app::APP_DATA_SUBDIR;
+
+    use super::*;
+    use crate::{
+        models::organization::OrganizationId,
+        tests::platform::{app_data, TestPlatform},
+        Platform, PlatformOptions,
+    };
+
+    const RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn recv_app(change_rx: &flume::Receiver<AppFileInput>) -> (String, bool) {
+        let input = change_rx
+            .recv_timeout(RECV_TIMEOUT)
+            .expect("receiving change");
+        (input.app_id, input.contents.is_empty())
+    }
+
+    #[test]
+    fn scan_and_watch() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let data_dir = base_dir.path().join(APP_DATA_SUBDIR);
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join("existing.json"), "{}").unwrap();
+        std::fs::write(data_dir.join("notes.txt"), "not app data").unwrap();
+
+        let (change_tx, change_rx) = flume::unbounded();
+        let source =
+            FsSource::new(vec![WatchedRoot::default_for(base_dir.path())], change_tx).unwrap();
+
+        // Files that were there before the watcher started are read right away.
+        assert_eq!(recv_app(&change_rx), ("existing".to_string(), false));
+
+        std::fs::write(data_dir.join("new.json"), "{}").unwrap();
+        assert_eq!(recv_app(&change_rx), ("new".to_string(), false));
+        assert!(source.status().is_running());
+
+        std::fs::remove_file(data_dir.join("new.json")).unwrap();
+        assert_eq!(recv_app(&change_rx), ("new".to_string(), true));
+
+        let status = source.status();
+        source.close();
+        assert!(!status.is_running());
+    }
+
+    #[test]
+    fn multiple_roots() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let shared_dir = base_dir.path().join("shared");
+        let organization_id = OrganizationId::new();
+        let roots = vec![
+            WatchedRoot {
+                app_id_prefix: "home-".to_string(),
+                ..WatchedRoot::default_for(base_dir.path())
+            },
+            WatchedRoot {
+                dir: shared_dir.clone(),
+                app_id_prefix: "shared-".to_string(),
+                owner: Some(AppOwner {
+                    organization_id,
+                    user_id: None,
+                }),
+            },
+        ];
+
+        let (change_tx, change_rx) = flume::unbounded();
+        let source = FsSource::new(roots, change_tx).unwrap();
+
+        // The same file name in each root becomes a different app.
+        let data_dir = base_dir.path().join(APP_DATA_SUBDIR);
+        std::fs::write(data_dir.join("news.json"), "{}").unwrap();
+        let input = change_rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        assert_eq!(input.app_id, "home-news");
+        assert!(input.owner.is_none());
+
+        std::fs::write(shared_dir.join("news.json"), "{}").unwrap();
+        let input = change_rx.recv_timeout(RECV_TIMEOUT).unwrap();
+        assert_eq!(input.app_id, "shared-news");
+        assert_eq!(
+            input.owner.map(|owner| owner.organization_id),
+            Some(organization_id)
+        );
+
+        source.close();
+    }
+
+    #[test]
+    fn path_for_app() {
+        let root = WatchedRoot {
+            dir: PathBuf::from("/shared"),
+            app_id_prefix: "shared-".to_string(),
+            owner: None,
+        };
+        assert_eq!(
+            root.path_for_app("shared-news"),
+            Some(PathBuf::from("/shared/news.json"))
+        );
+        assert_eq!(root.path_for_app("news"), None);
+        assert_eq!(root.path_for_app("shared-"), None);
+        assert_eq!(
+            root.app_id_for_path(Path::new("/shared/news.json")),
+            Some("shared-news".to_string())
+        );
+    }
+
+    #[test]
+    fn overlapping_prefixes() {
+        let root = |dir: &str, prefix: &str| WatchedRoot {
+            dir: PathBuf::from(dir),
+            app_id_prefix: prefix.to_string(),
+            owner: None,
+        };
+
+        // `shared-news.json` in the first root and `news.json` in the second would both be
+        // `shared-news`.
+        WatchedRoot::check_prefixes(&[root("/data", ""), root("/shared", "shared-")])
+            .expect_err("empty prefix overlaps");
+        WatchedRoot::check_prefixes(&[root("/a", "team-"), root("/b", "team-")])
+            .expect_err("same prefix overlaps");
+        WatchedRoot::check_prefixes(&[root("/data", "home-"), root("/shared", "shared-")]).unwrap();
+    }
+
+    #[test]
+    fn restart_delay() {
+        let mut delay = MIN_RESTART_DELAY;
+        let mut delays = Vec::new();
+        for _ in 0..8 {
+            delays.push(delay.as_secs());
+            delay = next_restart_delay(delay);
+        }
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[sqlx::test]
+    async fn remove_apps_deleted_while_stopped(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::new(pool.clone()).await;
+        let data = serde_json::json!({
+            "name": "Test App",
+            "path": "/bin/test",
+            "items": [],
+        });
+        assert!(platform.write_app_file("from-file", &data).await);
+        assert!(
+            platform
+                .send_app_data("from-http", app_data(data), false)
+                .await
+        );
+
+        // Stop watching before removing the file, as if the platform was not running.
+        let TestPlatform {
+            platform, base_dir, ..
+        } = platform;
+        let fs_source = platform.fs_source;
+        tokio::task::spawn_blocking(|| fs_source.close())
+            .await
+            .unwrap();
+        std::fs::remove_file(base_dir.path().join(APP_DATA_SUBDIR).join("from-file.json")).unwrap();
+
+        let platform = Platform::new(PlatformOptions {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            watched_roots: Vec::new(),
+            db: pool,
+            enable_scheduled_tasks: false,
+            interpreters: Default::default(),
+            api_url: None,
+        })
+        .await
+        .unwrap();
+
+        let apps = platform
+            .db
+            .get_apps(&["from-file".to_string(), "from-http".to_string()])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|app| app.id)
+            .collect::<Vec<_>>();
+        assert_eq!(apps, vec!["from-http"]);
+    }
+
+    #[sqlx::test]
+    async fn keep_apps_when_data_dir_is_missing(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::new(pool.clone()).await;
+        let data = serde_json::json!({
+            "name": "Test App",
+            "path": "/bin/test",
+            "items": [],
+        });
+        assert!(platform.write_app_file("from-file", &data).await);
+
+        let TestPlatform {
+            platform, base_dir, ..
+        } = platform;
+        let fs_source = platform.fs_source;
+        tokio::task::spawn_blocking(|| fs_source.close())
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(base_dir.path().join(APP_DATA_SUBDIR)).unwrap();
+
+        // The directory is gone, rather than the file, so the app is kept.
+        let platform = Platform::new(PlatformOptions {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            watched_roots: Vec::new(),
+            db: pool,
+            enable_scheduled_tasks: false,
+            interpreters: Default::default(),
+            api_url: None,
+        })
+        .await
+        .unwrap();
+
+        let apps = platform
+            .db
+            .get_apps(&["from-file".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(apps.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn keep_apps_outside_watched_roots(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::new(pool.clone()).await;
+        let data = serde_json::json!({
+            "name": "Test App",
+            "path": "/bin/test",
+            "items": [],
+        });
+        assert!(platform.write_app_file("from-file", &data).await);
+
+        let TestPlatform {
+            platform, base_dir, ..
+        } = platform;
+        let fs_source = platform.fs_source;
+        tokio::task::spawn_blocking(|| fs_source.close())
+            .await
+            .unwrap();
+
+        // Start again watching only a different directory, whose prefix the app doesn't have.
+        let platform = Platform::new(PlatformOptions {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            watched_roots: vec![WatchedRoot {
+                dir: base_dir.path().join("shared"),
+                app_id_prefix: "shared-".to_string(),
+                owner: None,
+            }],
+            db: pool,
+            enable_scheduled_tasks: false,
+            interpreters: Default::default(),
+            api_url: None,
+        })
+        .await
+        .unwrap();
+
+        let apps = platform
+            .db
+            .get_apps(&["from-file".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(apps.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn uninstall_prefixed_app(pool: sqlx::PgPool) {
+        let mut platform = TestPlatform::with_roots(pool, |base_dir| {
+            vec![WatchedRoot {
+                dir: base_dir.join("shared"),
+                app_id_prefix: "shared-".to_string(),
+                owner: None,
+            }]
+        })
+        .await;
+
+        let data = serde_json::json!({
+            "name": "Shared App",
+            "path": "/bin/shared",
+            "items": [],
+        });
+        let path = platform.base_dir.path().join("shared").join("news.json");
+        let tmp_path = platform.base_dir.path().join("news.json");
+        std::fs::write(&tmp_path, serde_json::to_vec(&data).unwrap()).unwrap();
+        std::fs::rename(&tmp_path, &path).unwrap();
+        assert!(platform.wait_for_change("shared-news").await);
+
+        assert!(platform
+            .platform
+            .db
+            .uninstall_app("shared-news")
+            .await
+            .unwrap());
+        assert!(!path.exists());
+        let apps = platform
+            .platform
+            .db
+            .get_apps(&["shared-news".to_string()])
+            .await
+            .unwrap();
+        assert!(apps.is_empty());
+    }
 }
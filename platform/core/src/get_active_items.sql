@@ -15,6 +15,14 @@ FROM
   LEFT JOIN item_notifications noti ON items.id = noti.item_id
     AND items.app_id = noti.app_id
     AND NOT noti.dismissed
+WHERE
+  items.app_id IN (
+    SELECT
+      id
+    FROM
+      apps
+    WHERE
+      enabled)
 GROUP BY
   items.id,
   items.app_id
@@ -0,0 +1,15 @@
+SELECT
+  id,
+  event_type AS "kind: FeedbackKind",
+  item_id AS "item_id!",
+  created_at
+FROM
+  events
+WHERE
+  app_id = $1
+  AND event_type IN ('dismiss_item', 'undismiss_item')
+  AND ($2::bigint IS NULL
+    OR id > $2)
+ORDER BY
+  id
+LIMIT $3
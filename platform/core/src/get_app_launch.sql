@@ -0,0 +1,10 @@
+SELECT
+  path,
+  command AS "command: Json<Vec<String>>",
+  working_dir,
+  output AS "output: AppOutput",
+  builtin_source AS "builtin_source: Json<BuiltinSource>"
+FROM
+  apps
+WHERE
+  id = $1
@@ -0,0 +1,16 @@
+SELECT
+  id AS "id: ScheduleId",
+  app_id,
+  cron,
+  arguments AS "arguments: Json<Vec<String>>",
+  timeout,
+  timezone,
+  last_run_at,
+  command AS "command: Json<Vec<String>>",
+  working_dir
+FROM
+  schedules
+WHERE
+  app_id = $1
+ORDER BY
+  position
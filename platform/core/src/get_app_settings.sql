@@ -0,0 +1,24 @@
+SELECT
+  apps.settings_schema AS "schema?",
+  (
+    SELECT
+      value
+    FROM
+      app_settings
+    WHERE
+      app_id = apps.id
+      AND organization_id = $2
+      AND user_id IS NULL) AS "organization?",
+  (
+    SELECT
+      value
+    FROM
+      app_settings
+    WHERE
+      app_id = apps.id
+      AND organization_id = $2
+      AND user_id = $3) AS "user?"
+FROM
+  apps
+WHERE
+  id = $1
@@ -1,8 +1,16 @@
 SELECT
   id,
   name,
-  path
+  path,
+  error,
+  enabled,
+  validation_errors AS "validation_errors: Json<Vec<ValidationIssue>>"
 FROM
   apps
 WHERE
   id = ANY ($1)
+  AND ($2::boolean
+    OR ((organization_id IS NULL
+        OR organization_id = $3::uuid)
+      AND (user_id IS NULL
+        OR user_id = $4::uuid)))
@@ -0,0 +1,27 @@
+-- Schedules with a timezone that are due to run, or which don't know their next run time yet.
+SELECT
+  schedules.id AS "id: ScheduleId",
+  schedules.app_id,
+  schedules.cron,
+  schedules.arguments AS "arguments: Json<Vec<String>>",
+  schedules.timeout,
+  schedules.timezone,
+  schedules.last_run_at,
+  schedules.next_run_at,
+  schedules.command AS "command: Json<Vec<String>>",
+  schedules.working_dir,
+  apps.path,
+  apps.command AS "app_command: Json<Vec<String>>",
+  apps.working_dir AS app_working_dir,
+  apps.output AS "output: AppOutput",
+  apps.builtin_source AS "builtin_source: Json<BuiltinSource>"
+FROM
+  schedules
+  JOIN apps ON apps.id = schedules.app_id
+WHERE
+  apps.enabled
+  AND schedules.timezone IS NOT NULL
+  AND (schedules.next_run_at IS NULL
+    OR schedules.next_run_at <= now())
+FOR UPDATE
+  OF schedules SKIP LOCKED
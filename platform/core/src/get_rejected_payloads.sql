@@ -0,0 +1,15 @@
+SELECT
+  id,
+  app_id,
+  source AS "source: AppDataSource",
+  merge_items,
+  contents,
+  error,
+  validation_errors AS "validation_errors: Json<Vec<ValidationIssue>>",
+  created_at
+FROM
+  rejected_payloads
+WHERE
+  app_id = $1
+ORDER BY
+  id DESC
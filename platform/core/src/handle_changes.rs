@@ -1,43 +1,269 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    panic::AssertUnwindSafe,
+    time::Instant,
+};
 
 use error_stack::{Report, ResultExt};
+use futures::FutureExt;
 use glance_app::AppData;
+use tokio::{sync::broadcast, task::JoinSet};
 use tracing::{event, instrument, Level};
 
-use crate::{db::Db, error::Error, items::Item, AppFileContents, AppFileInput};
+use crate::{
+    db::Db,
+    error::Error,
+    items::Item,
+    validation::{self, AppDataValidationError},
+    AppDataSource, AppFileContents, AppFileInput, AppOwner, ChangeProcessed,
+};
 
-pub async fn handle_changes(db: Db, change_rx: flume::Receiver<AppFileInput>) {
-    while let Ok(input) = change_rx.recv_async().await {
-        handle_change_or_error(&db, input).await;
+/// The most changes that can wait behind in-progress changes. Once this many are waiting, no
+/// more are taken from the change channel, so that it fills up and senders see the backpressure.
+pub(crate) const MAX_WAITING: usize = crate::CHANGE_QUEUE_SIZE;
+
+/// Process incoming app changes. Changes for different apps are handled in parallel, while
+/// changes for the same app are applied one at a time in the order they were received.
+/// Changes that pile up behind an in-progress change for the same app are coalesced with
+/// [enqueue_change].
+pub async fn handle_changes(
+    db: Db,
+    change_rx: flume::Receiver<AppFileInput>,
+    processed_tx: broadcast::Sender<ChangeProcessed>,
+) {
+    // Apps that have a change in progress, with any changes waiting behind it.
+    let mut in_progress: HashMap<String, VecDeque<AppFileInput>> = HashMap::new();
+    let mut waiting = 0;
+    let mut running = JoinSet::new();
+    let mut rx_open = true;
+
+    loop {
+        tokio::select! {
+            input = change_rx.recv_async(), if rx_open && waiting < MAX_WAITING => {
+                let Ok(input) = input else {
+                    rx_open = false;
+                    continue;
+                };
+
+                if let Some(queue) = in_progress.get_mut(&input.app_id) {
+                    let before = queue.len();
+                    enqueue_change(queue, input);
+                    waiting = waiting + queue.len() - before;
+                } else {
+                    in_progress.insert(input.app_id.clone(), VecDeque::new());
+                    spawn_change(&mut running, &db, &processed_tx, input);
+                }
+            }
+            Some(finished) = running.join_next() => {
+                // The task catches its own panics, so this only fails if the runtime is
+                // shutting down.
+                let app_id = match finished {
+                    Ok(app_id) => app_id,
+                    Err(e) => {
+                        event!(Level::ERROR, error = ?e, "Change task failed");
+                        continue;
+                    }
+                };
+                let next = in_progress.get_mut(&app_id).and_then(|queue| queue.pop_front());
+                if let Some(input) = next {
+                    waiting -= 1;
+                    spawn_change(&mut running, &db, &processed_tx, input);
+                } else {
+                    in_progress.remove(&app_id);
+                }
+            }
+            else => break,
+        }
+
+        metrics::gauge!("glance_change_queue_depth").set((change_rx.len() + waiting) as f64);
+        metrics::gauge!("glance_change_in_progress").set(running.len() as f64);
+    }
+}
+
+/// Add a change to an app's queue of waiting changes, coalescing it with the changes already
+/// there so that only the work needed to reach the final state is done.
+fn enqueue_change(queue: &mut VecDeque<AppFileInput>, mut input: AppFileInput) {
+    let before = queue.len();
+
+    if input.contents.is_empty() {
+        // Removing the app makes everything before it irrelevant.
+        queue.clear();
+    } else if !input.merge_items {
+        // A full snapshot replaces everything waiting except a pending removal, which also resets
+        // state such as dismissed items and so still needs to run.
+        let keep = queue
+            .iter()
+            .rposition(|waiting| waiting.contents.is_empty())
+            .map_or(0, |i| i + 1);
+        queue.truncate(keep);
+    } else if let Some(AppFileInput {
+        contents: AppFileContents::Parsed(existing),
+        source,
+        owner,
+        ..
+    }) = queue.back_mut()
+    {
+        match input.contents {
+            AppFileContents::Parsed(data) => {
+                merge_app_data(existing, *data);
+                *source = input.source;
+                if input.owner.is_some() {
+                    *owner = input.owner;
+                }
+                metrics::counter!("glance_change_coalesced_total").increment(1);
+                return;
+            }
+            contents => input.contents = contents,
+        }
+    }
+
+    metrics::counter!("glance_change_coalesced_total").increment((before - queue.len()) as u64);
+    queue.push_back(input);
+}
+
+/// Combine `newer` into `older`, with the same result as applying `newer` in merge mode after
+/// `older`.
+fn merge_app_data(older: &mut AppData, mut newer: AppData) {
+    let new_items = std::mem::take(&mut newer.items);
+
+    // Follow the same rule as the database, where metadata only moves forward in version.
+    if newer.version >= older.version {
+        let items = std::mem::take(&mut older.items);
+        *older = newer;
+        older.items = items;
     }
+
+    let mut positions = older
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| (item.id.clone(), i))
+        .collect::<HashMap<_, _>>();
+    for item in new_items {
+        match positions.get(&item.id) {
+            Some(&i) => older.items[i] = item,
+            None => {
+                positions.insert(item.id.clone(), older.items.len());
+                older.items.push(item);
+            }
+        }
+    }
+}
+
+fn spawn_change(
+    running: &mut JoinSet<String>,
+    db: &Db,
+    processed_tx: &broadcast::Sender<ChangeProcessed>,
+    input: AppFileInput,
+) {
+    let db = db.clone();
+    let processed_tx = processed_tx.clone();
+    let app_id = input.app_id.clone();
+    running.spawn(async move {
+        let start = Instant::now();
+        let result = AssertUnwindSafe(handle_change_or_error(&db, input))
+            .catch_unwind()
+            .await;
+        if result.is_err() {
+            event!(Level::ERROR, %app_id, "Panic while handling app change");
+            metrics::counter!("glance_app_change_errors_total", "app_id" => app_id.clone())
+                .increment(1);
+        }
+
+        metrics::histogram!("glance_change_processing_seconds", "app_id" => app_id.clone())
+            .record(start.elapsed().as_secs_f64());
+
+        // This only fails when nobody is listening, which is fine.
+        processed_tx
+            .send(ChangeProcessed {
+                app_id: app_id.clone(),
+                success: result.unwrap_or(false),
+            })
+            .ok();
+        app_id
+    });
 }
 
 #[instrument(skip(db, input), fields(app_id = %input.app_id, has_data = !input.contents.is_empty()))]
-async fn handle_change_or_error(db: &Db, input: AppFileInput) {
+async fn handle_change_or_error(db: &Db, input: AppFileInput) -> bool {
     let AppFileInput {
         app_id,
         contents,
         merge_items,
+        source,
+        owner,
     } = input;
 
-    let result = match contents {
+    let (result, raw_contents) = match contents {
         AppFileContents::Raw(contents) => {
-            handle_raw_data(db, &app_id, &contents, merge_items).await
+            let result =
+                handle_raw_data(db, &app_id, &contents, merge_items, source, owner.as_ref()).await;
+            (result, Some(contents))
+        }
+        AppFileContents::Parsed(data) => {
+            // Serialize before handing off the data so that it can be saved if it is rejected.
+            let raw_contents = serde_json::to_string(&data).ok();
+            let result =
+                handle_parsed_data(db, &app_id, *data, merge_items, source, owner.as_ref()).await;
+            (result, raw_contents)
         }
-        AppFileContents::Parsed(data) => handle_change(db, &app_id, *data, merge_items).await,
-        AppFileContents::Empty => handle_remove(db, &app_id).await,
+        AppFileContents::Empty => (handle_remove(db, &app_id).await, None),
     };
 
     let result = result.attach_printable_lazy(|| format!("App ID: {}", app_id));
+    let success = result.is_ok();
 
     if let Err(e) = result {
-        let err_desc = format!("{e:?}");
-        event!(Level::ERROR,  error = %err_desc , "Error handling app change");
-        let err_result = db
-            .update_app_status(&db.pool, &app_id, Some(&err_desc))
+        event!(Level::ERROR, error = ?e, "Error handling app change");
+        metrics::counter!("glance_app_change_errors_total", "app_id" => app_id.clone())
+            .increment(1);
+        record_rejected_change(
+            db,
+            &app_id,
+            source,
+            merge_items,
+            raw_contents.as_deref(),
+            &e,
+        )
+        .await;
+    }
+
+    success
+}
+
+/// Save the error on the app, and keep the contents of the update, if any, as a rejected payload.
+pub(crate) async fn record_rejected_change(
+    db: &Db,
+    app_id: &str,
+    source: AppDataSource,
+    merge_items: bool,
+    contents: Option<&str>,
+    error: &Report<Error>,
+) {
+    let err_desc = format!("{error:?}");
+    let validation_errors = error
+        .downcast_ref::<AppDataValidationError>()
+        .map(|v| v.issues.as_slice());
+    let err_result = db
+        .update_app_status(&db.pool, app_id, Some(&err_desc), validation_errors)
+        .await;
+    if let Err(e) = err_result {
+        event!(Level::ERROR,  error = ?e , "Failed to record app error");
+    }
+
+    if let Some(contents) = contents {
+        let rejected_result = db
+            .add_rejected_payload(
+                app_id,
+                source,
+                merge_items,
+                contents,
+                &err_desc,
+                validation_errors,
+            )
             .await;
-        if let Err(e) = err_result {
-            event!(Level::ERROR,  error = ?e , "Failed to record app error");
+        if let Err(e) = rejected_result {
+            event!(Level::ERROR,  error = ?e , "Failed to record rejected payload");
         }
     }
 }
@@ -47,9 +273,23 @@ async fn handle_raw_data(
     app_id: &str,
     contents: &str,
     merge_items: bool,
+    source: AppDataSource,
+    owner: Option<&AppOwner>,
 ) -> Result<(), Report<Error>> {
-    let data = serde_json::from_str::<AppData>(contents).change_context(Error::ReadAppData)?;
-    handle_change(db, app_id, data, merge_items).await
+    let data = validation::parse_app_data(contents).change_context(Error::ReadAppData)?;
+    handle_change(db, app_id, data, merge_items, source, owner).await
+}
+
+async fn handle_parsed_data(
+    db: &Db,
+    app_id: &str,
+    data: AppData,
+    merge_items: bool,
+    source: AppDataSource,
+    owner: Option<&AppOwner>,
+) -> Result<(), Report<Error>> {
+    validation::validate_app_data(&data).change_context(Error::ReadAppData)?;
+    handle_change(db, app_id, data, merge_items, source, owner).await
 }
 
 pub async fn handle_change(
@@ -57,6 +297,8 @@ pub async fn handle_change(
     app_id: &str,
     mut app: AppData,
     merge_items: bool,
+    source: AppDataSource,
+    owner: Option<&AppOwner>,
 ) -> Result<(), Report<Error>> {
     let current_items = db
         .read_app_items(app_id)
@@ -72,25 +314,28 @@ pub async fn handle_change(
         .collect::<Vec<_>>();
 
     let items = std::mem::replace(&mut app.items, vec![]);
-    let changed_items = items.into_iter().map(|item| {
-        let resurface = if let Some(current_item) = current_items.get(&item.id) {
-            current_item.changed_from(&item)
-        } else {
-            true
-        };
-
-        let item = Item::from_app_item(app_id.to_string(), item);
-        (item, resurface)
-    });
+    let changed_items = items
+        .into_iter()
+        .filter_map(|item| {
+            let resurface = match current_items.get(&item.id) {
+                // Skip the write entirely so that the item and its event log are left alone.
+                Some(current_item) if current_item.same_data_as(&item) => return None,
+                Some(current_item) => current_item.changed_from(&item),
+                None => true,
+            };
+
+            let item = Item::from_app_item(app_id.to_string(), item);
+            Some((item, resurface))
+        })
+        .collect::<Vec<_>>();
 
     let mut tx = db.pool.begin().await.change_context(Error::Db)?;
 
-    db.create_or_update_app(tx.as_mut(), app_id, &app).await?;
+    db.create_or_update_app(tx.as_mut(), app_id, &app, Some(source), owner)
+        .await?;
 
-    for (item, resurface) in changed_items {
-        db.create_or_update_item(tx.as_mut(), &item, resurface)
-            .await?;
-    }
+    db.create_or_update_items(tx.as_mut(), app_id, &changed_items)
+        .await?;
 
     if !merge_items {
         db.remove_unfound_items(tx.as_mut(), app_id, &item_ids)
@@ -105,3 +350,6 @@ pub async fn handle_change(
 async fn handle_remove(db: &Db, app_id: &str) -> Result<(), Report<Error>> {
     db.remove_app(app_id).await
 }
+
+#[cfg(test)]
+mod tests;
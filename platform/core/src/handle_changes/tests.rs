@@ -0,0 +1,470 @@
+use glance_app::AppOutput;
+use sqlx::PgPool;
+
+use super::*;
+use crate::{
+    scheduled_task::AppLaunch,
+    tests::platform::{app_data, TestPlatform},
+    AppDataSource,
+};
+
+fn versioned_app_data(version: u32, item_ids: &[&str]) -> Box<AppData> {
+    let items = item_ids
+        .iter()
+        .map(|id| {
+            serde_json::json!({
+                "id": id,
+                "data": { "title": format!("{id} v{version}") },
+                "updated": "2024-01-01T00:00:00Z",
+            })
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::from_value(serde_json::json!({
+        "name": format!("App v{version}"),
+        "path": "/bin/test",
+        "items": items,
+        "version": version,
+    }))
+    .unwrap()
+}
+
+fn input(contents: AppFileContents, merge_items: bool) -> AppFileInput {
+    AppFileInput {
+        app_id: "test".to_string(),
+        contents,
+        merge_items,
+        source: AppDataSource::Http,
+        owner: None,
+    }
+}
+
+fn parsed(contents: &AppFileContents) -> &AppData {
+    match contents {
+        AppFileContents::Parsed(data) => data,
+        _ => panic!("expected parsed data"),
+    }
+}
+
+fn item_titles(data: &AppData) -> Vec<&str> {
+    data.items
+        .iter()
+        .map(|item| item.data.title.as_str())
+        .collect()
+}
+
+#[test]
+fn full_snapshot_replaces_waiting_changes() {
+    let mut queue = VecDeque::new();
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(1, &["a"])),
+            false,
+        ),
+    );
+    enqueue_change(
+        &mut queue,
+        input(AppFileContents::Parsed(versioned_app_data(1, &["b"])), true),
+    );
+    enqueue_change(
+        &mut queue,
+        input(AppFileContents::Raw("{}".to_string()), false),
+    );
+
+    assert_eq!(queue.len(), 1);
+    assert!(matches!(queue[0].contents, AppFileContents::Raw(_)));
+}
+
+#[test]
+fn full_snapshot_keeps_pending_removal() {
+    let mut queue = VecDeque::new();
+    enqueue_change(&mut queue, input(AppFileContents::Empty, false));
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(1, &["a"])),
+            false,
+        ),
+    );
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(1, &["b"])),
+            false,
+        ),
+    );
+
+    assert_eq!(queue.len(), 2);
+    assert!(queue[0].contents.is_empty());
+    assert_eq!(item_titles(parsed(&queue[1].contents)), vec!["b v1"]);
+}
+
+#[test]
+fn removal_replaces_waiting_changes() {
+    let mut queue = VecDeque::new();
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(1, &["a"])),
+            false,
+        ),
+    );
+    enqueue_change(&mut queue, input(AppFileContents::Empty, false));
+
+    assert_eq!(queue.len(), 1);
+    assert!(queue[0].contents.is_empty());
+}
+
+#[test]
+fn merge_combines_with_full_snapshot() {
+    let mut queue = VecDeque::new();
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(2, &["a", "b"])),
+            false,
+        ),
+    );
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(1, &["b", "c"])),
+            true,
+        ),
+    );
+
+    assert_eq!(queue.len(), 1);
+    assert!(!queue[0].merge_items);
+    let data = parsed(&queue[0].contents);
+    // The older metadata version wins
+    assert_eq!(data.name, "App v2");
+    assert_eq!(item_titles(data), vec!["a v2", "b v1", "c v1"]);
+}
+
+#[test]
+fn merges_combine() {
+    let mut queue = VecDeque::new();
+    enqueue_change(
+        &mut queue,
+        input(AppFileContents::Parsed(versioned_app_data(1, &["a"])), true),
+    );
+    enqueue_change(
+        &mut queue,
+        input(
+            AppFileContents::Parsed(versioned_app_data(2, &["a", "b"])),
+            true,
+        ),
+    );
+
+    assert_eq!(queue.len(), 1);
+    assert!(queue[0].merge_items);
+    let data = parsed(&queue[0].contents);
+    assert_eq!(data.name, "App v2");
+    assert_eq!(item_titles(data), vec!["a v2", "b v2"]);
+}
+
+// Tests for the full pipeline, running against a database and a temporary data directory.
+
+fn item_json(id: &str, title: &str, state_key: Option<&str>) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "data": { "title": title },
+        "state_key": state_key,
+        "updated": "2024-01-01T00:00:00Z",
+    })
+}
+
+fn app_json(name: &str, items: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "path": "/bin/test",
+        "items": items,
+    })
+}
+
+async fn item_ids(db: &Db, app_id: &str) -> Vec<String> {
+    let mut ids = db
+        .read_app_items(app_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|item| item.id)
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+async fn read_item(db: &Db, app_id: &str, item_id: &str) -> Item {
+    db.read_app_items(app_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|item| item.id == item_id)
+        .expect("item should exist")
+}
+
+#[sqlx::test]
+async fn add_app_from_file(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let items = vec![
+        item_json("a", "Item A", None),
+        item_json("b", "Item B", None),
+    ];
+    assert!(
+        platform
+            .write_app_file("file-app", &app_json("File App", items))
+            .await
+    );
+
+    let db = &platform.platform.db;
+    let apps = db.get_apps(&["file-app".to_string()]).await.unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0].name, "File App");
+    assert_eq!(item_ids(db, "file-app").await, vec!["a", "b"]);
+}
+
+#[sqlx::test]
+async fn resurface_stateless_items(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let data = app_json("App", vec![item_json("a", "Title", None)]);
+    assert!(platform.write_app_file("app", &data).await);
+    db.set_item_dismissed("app", "a", true).await.unwrap();
+
+    // Writing the same data again leaves the item dismissed
+    let data = app_json("App v2", vec![item_json("a", "Title", None)]);
+    assert!(platform.write_app_file("app", &data).await);
+    assert!(read_item(&db, "app", "a").await.dismissed);
+
+    // Changing the item resurfaces it
+    let data = app_json("App", vec![item_json("a", "New Title", None)]);
+    assert!(platform.write_app_file("app", &data).await);
+    let item = read_item(&db, "app", "a").await;
+    assert!(!item.dismissed);
+    assert_eq!(item.data.title, "New Title");
+}
+
+#[sqlx::test]
+async fn resurface_with_state_key(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let data = app_json("App", vec![item_json("a", "Title", Some("1"))]);
+    assert!(platform.write_app_file("app", &data).await);
+    db.set_item_dismissed("app", "a", true).await.unwrap();
+
+    // The data is updated, but the item stays dismissed while the state key is unchanged.
+    let data = app_json("App", vec![item_json("a", "New Title", Some("1"))]);
+    assert!(platform.write_app_file("app", &data).await);
+    let item = read_item(&db, "app", "a").await;
+    assert!(item.dismissed);
+    assert_eq!(item.data.title, "New Title");
+
+    let data = app_json("App", vec![item_json("a", "New Title", Some("2"))]);
+    assert!(platform.write_app_file("app", &data).await);
+    assert!(!read_item(&db, "app", "a").await.dismissed);
+}
+
+#[sqlx::test]
+async fn merge_mode(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let data = app_data(app_json(
+        "App",
+        vec![item_json("a", "A", None), item_json("b", "B", None)],
+    ));
+    assert!(platform.send_app_data("app", data, false).await);
+
+    let data = app_data(app_json("App", vec![item_json("c", "C", None)]));
+    assert!(platform.send_app_data("app", data, true).await);
+    assert_eq!(item_ids(&db, "app").await, vec!["a", "b", "c"]);
+
+    // A full update removes the items that are not present
+    let data = app_data(app_json("App", vec![item_json("c", "C", None)]));
+    assert!(platform.send_app_data("app", data, false).await);
+    assert_eq!(item_ids(&db, "app").await, vec!["c"]);
+}
+
+#[sqlx::test]
+async fn remove_app_when_file_deleted(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let data = app_json("App", vec![item_json("a", "A", None)]);
+    assert!(platform.write_app_file("app", &data).await);
+    assert!(platform.remove_app_file("app").await);
+
+    assert!(db.get_apps(&["app".to_string()]).await.unwrap().is_empty());
+    assert!(item_ids(&db, "app").await.is_empty());
+}
+
+#[sqlx::test]
+async fn version_rollback_protection(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let mut data = app_json("New Name", vec![item_json("a", "A", None)]);
+    data["version"] = 2.into();
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+
+    let mut data = app_json("Old Name", vec![item_json("b", "B", None)]);
+    data["version"] = 1.into();
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+
+    let apps = db.get_apps(&["app".to_string()]).await.unwrap();
+    assert_eq!(apps[0].name, "New Name");
+    // The version does not apply to items
+    assert_eq!(item_ids(&db, "app").await, vec!["b"]);
+}
+
+#[sqlx::test]
+async fn register_scheduled_jobs(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let mut data = app_json("App", vec![]);
+    data["schedule"] = serde_json::json!([{ "cron": "0 */15 * * * *" }]);
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+
+    let schedules = db.get_app_schedules(&db.pool, "app").await.unwrap();
+    assert_eq!(schedules.len(), 1);
+    let jobs = db
+        .task_queue
+        .list_recurring_jobs_with_prefix("app:")
+        .await
+        .unwrap();
+    assert_eq!(jobs, vec![schedules[0].job_id()]);
+
+    // Schedules with the same cron spec but different arguments get separate jobs, and the
+    // existing schedule keeps its ID.
+    let mut data = app_json("App", vec![]);
+    data["schedule"] = serde_json::json!([
+        { "cron": "0 */15 * * * *" },
+        { "cron": "0 */15 * * * *", "arguments": ["--full"] }
+    ]);
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+
+    let new_schedules = db.get_app_schedules(&db.pool, "app").await.unwrap();
+    assert_eq!(new_schedules.len(), 2);
+    assert_eq!(new_schedules[0].id, schedules[0].id);
+    assert_eq!(new_schedules[1].arguments.0, vec!["--full"]);
+    let mut jobs = db
+        .task_queue
+        .list_recurring_jobs_with_prefix("app:")
+        .await
+        .unwrap();
+    jobs.sort();
+    let mut expected = new_schedules.iter().map(|s| s.job_id()).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(jobs, expected);
+
+    // Removing the schedule removes the job
+    let data = app_json("App", vec![]);
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+    let jobs = db
+        .task_queue
+        .list_recurring_jobs_with_prefix("app:")
+        .await
+        .unwrap();
+    assert!(jobs.is_empty());
+    assert!(db
+        .get_app_schedules(&db.pool, "app")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[sqlx::test]
+async fn schedule_command_and_working_dir(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let mut data = app_json("App", vec![]);
+    data["schedule"] = serde_json::json!([
+        { "cron": "0 */15 * * * *", "command": ["bun", "run", "full.ts"], "working_dir": "/srv/app" },
+        { "cron": "0 0 * * * *" }
+    ]);
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+
+    let schedules = db.get_app_schedules(&db.pool, "app").await.unwrap();
+    assert_eq!(schedules.len(), 2);
+    assert_eq!(
+        schedules[0].command.as_ref().map(|c| c.0.clone()),
+        Some(vec![
+            "bun".to_string(),
+            "run".to_string(),
+            "full.ts".to_string()
+        ])
+    );
+    assert_eq!(schedules[0].working_dir.as_deref(), Some("/srv/app"));
+    assert!(schedules[1].command.is_none());
+    assert!(schedules[1].working_dir.is_none());
+}
+
+#[sqlx::test]
+async fn reconcile_lost_jobs(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let mut data = app_json("App", vec![]);
+    data["schedule"] = serde_json::json!([{ "cron": "0 */15 * * * *" }]);
+    assert!(platform.send_app_data("app", app_data(data), false).await);
+    let schedules = db.get_app_schedules(&db.pool, "app").await.unwrap();
+
+    // Simulate a lost or stale task queue.
+    db.task_queue
+        .delete_recurring_job(schedules[0].job_id())
+        .await
+        .unwrap();
+    let mut stale = schedules[0].clone();
+    stale.app_id = "stale".to_string();
+    let launch = AppLaunch {
+        path: "/bin/stale".to_string(),
+        command: None,
+        working_dir: None,
+        output: AppOutput::File,
+        builtin_source: None,
+    };
+    db.sync_scheduled_jobs("stale", Some(&launch), &[stale])
+        .await
+        .unwrap();
+
+    db.reconcile_scheduled_jobs().await.unwrap();
+
+    let jobs = db
+        .task_queue
+        .list_recurring_jobs_with_prefix("")
+        .await
+        .unwrap();
+    assert_eq!(jobs, vec![schedules[0].job_id()]);
+}
+
+#[sqlx::test]
+async fn record_invalid_data(pool: PgPool) {
+    let mut platform = TestPlatform::new(pool).await;
+    let db = platform.platform.db.clone();
+
+    let data = app_json("App", vec![item_json("a", "A", None)]);
+    assert!(platform.write_app_file("app", &data).await);
+
+    let data = app_json(
+        "App",
+        vec![item_json("a", "A", None), item_json("a", "A again", None)],
+    );
+    assert!(!platform.write_app_file("app", &data).await);
+
+    let app = db.get_apps(&["app".to_string()]).await.unwrap().remove(0);
+    assert!(app.error.is_some());
+    let validation_errors = app.validation_errors.expect("validation errors").0;
+    assert_eq!(validation_errors[0].path, "/items/1/id");
+
+    let rejected = db.get_rejected_payloads("app").await.unwrap();
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].source, AppDataSource::File);
+
+    // The existing items are left alone
+    assert_eq!(item_ids(&db, "app").await, vec!["a"]);
+}
@@ -1,13 +1,21 @@
 use glance_app::{AppItem, AppItemData, Notification};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{types::Json, FromRow};
 use tracing::instrument;
 
+use crate::validation::ValidationIssue;
+
 #[derive(Debug, Serialize)]
 pub struct AppInfo {
     pub id: String,
     pub name: String,
     pub path: String,
+    /// The error from the last failed update, if any
+    pub error: Option<String>,
+    /// Disabled apps do not run on their schedule and their items are hidden
+    pub enabled: bool,
+    /// Located validation problems from the last failed update, if any
+    pub validation_errors: Option<Json<Vec<ValidationIssue>>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,6 +58,18 @@ impl Item {
         }
     }
 
+    /// Convert the item into the form that is passed back to the app on its next run.
+    pub fn into_previous_item(self) -> glance_app::PreviousItem {
+        glance_app::PreviousItem {
+            id: self.id,
+            data: self.data,
+            state_key: self.state_key,
+            persistent: self.persistent,
+            dismissed: self.dismissed,
+            updated: self.updated_at,
+        }
+    }
+
     /// Check if this item is considered changed from another item, using the state key if it is
     /// set and comparing individual fields otherwise.
     #[instrument(level = "trace")]
@@ -58,7 +78,26 @@ impl Item {
             (Some(a), Some(b)) => a != b,
             (Some(_), None) => true,
             (None, Some(_)) => true,
-            (None, None) => self.equal_stateless(other),
+            (None, None) => !self.equal_stateless(other),
+        }
+    }
+
+    /// Check if this item has exactly the same data as a newly-submitted item, so that
+    /// writing the new item would not change anything. Unlike [Item::changed_from], this
+    /// always compares the full data, even when a state key is present.
+    pub fn same_data_as(&self, other: &AppItem) -> bool {
+        if self.persistent != other.persistent || self.state_key != other.state_key {
+            return false;
+        }
+
+        // Compare through `serde_json::Value` so that the raw `data` field is compared by value
+        // and not by its formatting.
+        match (
+            serde_json::to_value(&self.data),
+            serde_json::to_value(&other.data),
+        ) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
         }
     }
 
@@ -80,3 +119,46 @@ impl Item {
         // && self.charts == other.charts
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_item(title: &str, state_key: Option<&str>) -> AppItem {
+        serde_json::from_value(serde_json::json!({
+            "id": "item",
+            "data": { "title": title },
+            "state_key": state_key,
+            "updated": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    fn stored(title: &str, state_key: Option<&str>) -> Item {
+        Item::from_app_item("app".to_string(), app_item(title, state_key))
+    }
+
+    #[test]
+    fn changed_from_without_state_key() {
+        let item = stored("Title", None);
+        assert!(!item.changed_from(&app_item("Title", None)));
+        assert!(item.changed_from(&app_item("New Title", None)));
+    }
+
+    #[test]
+    fn changed_from_with_state_key() {
+        let item = stored("Title", Some("1"));
+        assert!(!item.changed_from(&app_item("New Title", Some("1"))));
+        assert!(item.changed_from(&app_item("Title", Some("2"))));
+        assert!(item.changed_from(&app_item("Title", None)));
+        assert!(stored("Title", None).changed_from(&app_item("Title", Some("1"))));
+    }
+
+    #[test]
+    fn same_data_as() {
+        let item = stored("Title", Some("1"));
+        assert!(item.same_data_as(&app_item("Title", Some("1"))));
+        assert!(!item.same_data_as(&app_item("New Title", Some("1"))));
+        assert!(!item.same_data_as(&app_item("Title", Some("2"))));
+    }
+}
@@ -1,6 +1,9 @@
 //! Glance platform core
 #![warn(missing_docs)]
+mod app_settings;
+mod archive;
 pub mod auth;
+mod builtin_sources;
 pub mod cmd;
 pub mod db;
 pub mod emails;
@@ -11,7 +14,10 @@ mod fs_source;
 mod handle_changes;
 mod items;
 pub mod models;
+pub mod platform_health;
+mod rejected_payload;
 mod scheduled_task;
+mod schedules;
 /// The HTTP server
 pub mod server;
 #[cfg(test)]
@@ -19,14 +25,18 @@ pub mod tests;
 /// Tracing setup
 pub mod tracing_config;
 pub mod users;
+mod validation;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use db::{Db, DbInner};
 pub use error::Error;
 use error_stack::{Report, ResultExt};
-use glance_app::{App, AppData};
+use glance_app::{App, AppData, APP_DATA_SUBDIR};
+use models::{organization::OrganizationId, user::UserId};
+use platform_health::{PlatformHealth, WorkerStatus};
 use scheduled_task::create_scheduled_task_runner;
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use tracing::{event, Level};
 
@@ -49,23 +59,176 @@ impl AppFileContents {
     }
 }
 
+/// Where an app data update came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "app_data_source", rename_all = "snake_case")]
+pub enum AppDataSource {
+    /// A file in the app data directory
+    File,
+    /// The HTTP API
+    Http,
+    /// A scheduled run of the app, either its standard output or a poll of its built-in source
+    ScheduledRun,
+}
+
+/// The organization, and optionally the user within it, that an app belongs to
+#[derive(Clone, Debug)]
+pub struct AppOwner {
+    /// The organization that owns the app
+    pub organization_id: OrganizationId,
+    /// The user that owns the app, or `None` if it belongs to the whole organization
+    pub user_id: Option<UserId>,
+}
+
+/// A directory that is watched for app data files
+#[derive(Clone, Debug)]
+pub struct WatchedRoot {
+    /// The directory to watch
+    pub dir: PathBuf,
+    /// Added to the start of each file's name to form its app ID, so that files with the same
+    /// name in different roots become different apps.
+    pub app_id_prefix: String,
+    /// The owner of the apps read from this directory, if any
+    pub owner: Option<AppOwner>,
+}
+
+impl WatchedRoot {
+    /// The data directory inside the base directory, which is watched when no roots are
+    /// configured.
+    pub fn default_for(base_dir: &Path) -> Self {
+        Self {
+            dir: base_dir.join(APP_DATA_SUBDIR),
+            app_id_prefix: String::new(),
+            owner: None,
+        }
+    }
+
+    /// Parse a root given as `DIR[,prefix=PREFIX][,org=ORG_ID][,user=USER_ID]`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = value.split(',');
+        let dir = parts.next().unwrap_or_default().trim();
+        if dir.is_empty() {
+            return Err(format!("Expected a directory, got {value}"));
+        }
+
+        let mut app_id_prefix = String::new();
+        let mut organization_id = None;
+        let mut user_id = None;
+        for part in parts {
+            let (key, option) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected KEY=VALUE, got {part}"))?;
+            let option = option.trim();
+            match key.trim() {
+                "prefix" => app_id_prefix = option.to_string(),
+                "org" => {
+                    let id = option
+                        .parse::<OrganizationId>()
+                        .map_err(|_| format!("Invalid organization ID {option}"))?;
+                    organization_id = Some(id);
+                }
+                "user" => {
+                    let id = option
+                        .parse::<UserId>()
+                        .map_err(|_| format!("Invalid user ID {option}"))?;
+                    user_id = Some(id);
+                }
+                key => return Err(format!("Unknown option {key}")),
+            }
+        }
+
+        let owner = match (organization_id, user_id) {
+            (Some(organization_id), user_id) => Some(AppOwner {
+                organization_id,
+                user_id,
+            }),
+            (None, Some(_)) => return Err("A user owner also requires `org`".to_string()),
+            (None, None) => None,
+        };
+
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            app_id_prefix,
+            owner,
+        })
+    }
+
+    /// Check that no app ID prefix starts with another one. Otherwise the same app ID could come
+    /// from more than one root, such as `shared-news.json` in a root with no prefix and
+    /// `news.json` in a root with the prefix `shared-`.
+    pub fn check_prefixes(roots: &[WatchedRoot]) -> Result<(), Report<Error>> {
+        for (i, a) in roots.iter().enumerate() {
+            for b in &roots[i + 1..] {
+                if a.app_id_prefix.starts_with(&b.app_id_prefix)
+                    || b.app_id_prefix.starts_with(&a.app_id_prefix)
+                {
+                    return Err(Report::new(Error::Config)).attach_printable(format!(
+                        "The app ID prefixes of {} and {} overlap",
+                        a.dir.display(),
+                        b.dir.display()
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Where the data file for an app would be if it came from this directory.
+    pub(crate) fn path_for_app(&self, app_id: &str) -> Option<PathBuf> {
+        app_id
+            .strip_prefix(&self.app_id_prefix)
+            .filter(|name| !name.is_empty())
+            .map(|name| self.dir.join(format!("{name}.json")))
+    }
+}
+
+/// How many app changes can be sent to the change handler before senders have to wait
+pub(crate) const CHANGE_QUEUE_SIZE: usize = 16;
+
+/// The subdirectory of the base directory which holds the logs from scheduled app runs
+pub(crate) const LOG_SUBDIR: &str = "logs";
+
 /// Input to the platform of an app's data, to be reconciled against the existing data.
 pub struct AppFileInput {
     app_id: String,
     contents: AppFileContents,
     merge_items: bool,
+    source: AppDataSource,
+    /// The owner to record on the app. Changes without an owner leave the app's current owner
+    /// alone.
+    owner: Option<AppOwner>,
+}
+
+/// Sent after the platform finishes processing an [AppFileInput]
+#[derive(Clone, Debug)]
+pub struct ChangeProcessed {
+    /// The app that was updated
+    pub app_id: String,
+    /// False if the change could not be applied
+    pub success: bool,
 }
 
 /// Configuration for the platform
 pub struct PlatformOptions {
     /// Override the data directory
     pub base_dir: Option<PathBuf>,
+    /// Directories to watch for app data files. If empty, the `data` subdirectory of the base
+    /// directory is watched.
+    pub watched_roots: Vec<WatchedRoot>,
     /// The database pool
     pub db: PgPool,
     /// true to enable running scheduled tasks
     /// Schedule task configuration will still be updated when this is disabled; this only
     /// controls instantiation of the queue worker.
     pub enable_scheduled_tasks: bool,
+    /// Commands to run apps through, keyed by file extension, for apps that don't declare
+    /// their own command.
+    pub interpreters: scheduled_task::Interpreters,
+    /// The base URL of the platform's API, which is passed to scheduled apps. If not set, apps
+    /// use their default.
+    pub api_url: Option<String>,
 }
 
 /// The platform data
@@ -75,9 +238,12 @@ pub struct Platform {
     change_handler: tokio::task::JoinHandle<()>,
     /// Send app updates to the change handler task
     pub change_tx: flume::Sender<AppFileInput>,
+    change_processed_tx: tokio::sync::broadcast::Sender<ChangeProcessed>,
     /// The database for the platform
     pub db: Db,
     scheduled_task_runner: Option<effectum::Worker>,
+    /// Whether the platform's background workers are running
+    pub health: PlatformHealth,
 }
 
 impl Platform {
@@ -85,53 +251,90 @@ impl Platform {
     pub async fn new(config: PlatformOptions) -> Result<Self, Report<Error>> {
         let base_dir = config.base_dir.unwrap_or_else(App::base_data_dir);
         std::fs::create_dir_all(&base_dir).expect("creating data directory");
-        let (change_tx, change_rx) = flume::bounded(16);
+        let (change_tx, change_rx) = flume::bounded(CHANGE_QUEUE_SIZE);
 
-        let db = DbInner::new(config.db, &base_dir)
-            .await
-            .expect("creating database");
+        let db = DbInner::new(config.db, &base_dir, config.watched_roots).await?;
         let db = std::sync::Arc::new(db);
 
-        let log_dir = base_dir.join("logs");
+        #[cfg(feature = "fs-source")]
+        fs_source::remove_deleted_apps(&db, &db.watched_roots).await?;
+
+        // The task queue is kept separately from the database, so make sure that it matches.
+        db.reconcile_scheduled_jobs().await?;
+
+        let log_dir = base_dir.join(LOG_SUBDIR);
         std::fs::create_dir_all(&log_dir).expect("creating logs directory");
 
         let scheduled_task_runner = if config.enable_scheduled_tasks {
-            let runner = create_scheduled_task_runner(db.clone(), log_dir)
-                .await
-                .change_context(Error::TaskQueue)?;
+            let runner = create_scheduled_task_runner(
+                db.clone(),
+                log_dir,
+                config.interpreters,
+                config.api_url,
+                change_tx.clone(),
+            )
+            .await
+            .change_context(Error::TaskQueue)?;
             Some(runner)
         } else {
             None
         };
 
-        let change_handler =
-            tokio::task::spawn(handle_changes::handle_changes(db.clone(), change_rx));
+        let (change_processed_tx, _) = tokio::sync::broadcast::channel(64);
+        let (change_handler_status, change_handler_guard) = WorkerStatus::start();
+        let change_handler = tokio::task::spawn({
+            let changes =
+                handle_changes::handle_changes(db.clone(), change_rx, change_processed_tx.clone());
+            async move {
+                let _guard = change_handler_guard;
+                changes.await
+            }
+        });
+
+        #[cfg(feature = "fs-source")]
+        let fs_source = fs_source::FsSource::new(db.watched_roots.clone(), change_tx.clone())
+            .expect("creating FsSource");
+
+        let health = PlatformHealth {
+            #[cfg(feature = "fs-source")]
+            fs_source: Some(fs_source.status()),
+            #[cfg(not(feature = "fs-source"))]
+            fs_source: None,
+            change_handler: change_handler_status,
+        };
 
         Ok(Self {
             #[cfg(feature = "fs-source")]
-            fs_source: fs_source::FsSource::new(base_dir, change_tx.clone())
-                .expect("creating FsSource"),
+            fs_source,
             change_handler,
             change_tx,
+            change_processed_tx,
             db,
             scheduled_task_runner,
+            health,
         })
     }
 
+    /// Receive a notification each time the platform finishes processing an app change.
+    pub fn subscribe_changes(&self) -> tokio::sync::broadcast::Receiver<ChangeProcessed> {
+        self.change_processed_tx.subscribe()
+    }
+
     /// Wait for everything to settle and then shut down.
     pub async fn shutdown(self) {
         let Self {
             fs_source,
             change_handler,
+            change_tx,
+            change_processed_tx: _,
             db,
             scheduled_task_runner,
-            ..
+            health: _,
         } = self;
         event!(Level::DEBUG, "Shutting down fs source");
         #[cfg(feature = "fs-source")]
         tokio::task::spawn_blocking(|| fs_source.close()).await.ok();
-        event!(Level::DEBUG, "Shutting down change handler");
-        change_handler.await.ok();
+        // Scheduled runs can send changes, so stop them before waiting for the change handler.
         event!(Level::DEBUG, "Shutting down scheduled task runner");
         if let Some(runner) = scheduled_task_runner {
             runner
@@ -139,6 +342,10 @@ impl Platform {
                 .await
                 .ok();
         }
+        // The change handler finishes once every sender is gone.
+        drop(change_tx);
+        event!(Level::DEBUG, "Shutting down change handler");
+        change_handler.await.ok();
         db.task_queue
             .close(std::time::Duration::from_secs(10))
             .await
@@ -0,0 +1,17 @@
+SELECT
+  id,
+  name,
+  path,
+  error,
+  enabled,
+  validation_errors AS "validation_errors: Json<Vec<ValidationIssue>>"
+FROM
+  apps
+WHERE
+  $1::boolean
+  OR ((organization_id IS NULL
+      OR organization_id = $2::uuid)
+    AND (user_id IS NULL
+      OR user_id = $3::uuid))
+ORDER BY
+  id
@@ -7,7 +7,9 @@ use filigree::{
     auth::{CorsSetting, SameSiteArg, SessionCookieBuilder},
     tracing_config::{configure_tracing, teardown_tracing, TracingProvider},
 };
-use glance_core::{cmd, db, emails, server, tracing_config, Error, Platform, PlatformOptions};
+use glance_core::{
+    cmd, db, emails, server, tracing_config, Error, Platform, PlatformOptions, WatchedRoot,
+};
 use tracing::{event, Level};
 
 #[derive(Parser)]
@@ -22,6 +24,8 @@ enum Command {
     Util(cmd::util::UtilCommand),
 
     Db(cmd::db::DbCommand),
+    Apps(cmd::apps::AppsCommand),
+    Items(cmd::items::ItemsCommand),
     Serve(ServeCommand),
 }
 
@@ -30,6 +34,18 @@ struct ServeCommand {
     #[clap(long, env = "GLANCE_BASE_DIR")]
     base_dir: Option<PathBuf>,
 
+    /// Watch a directory for app data files, given as
+    /// `DIR[,prefix=PREFIX][,org=ORG_ID][,user=USER_ID]`. The prefix is added to the start of
+    /// each app ID from the directory, and `org` and `user` set the owner of those apps. If
+    /// omitted, the `data` directory inside the base directory is watched.
+    #[clap(
+        long = "watch-dir",
+        env = "GLANCE_WATCH_DIRS",
+        value_delimiter = ';',
+        value_parser = WatchedRoot::parse
+    )]
+    watched_roots: Vec<WatchedRoot>,
+
     /// The PostgreSQL database to connect to
     #[clap(long = "db", env = "GLANCE_DATABASE_URL")]
     database_url: String,
@@ -42,6 +58,11 @@ struct ServeCommand {
     #[clap(long, env = "GLANCE_PORT", default_value_t = 6749)]
     port: u16,
 
+    /// The base URL of this server's API, which scheduled apps use to call back into the
+    /// platform. Defaults to `http://localhost:PORT/api`.
+    #[clap(long, env = "GLANCE_API_URL")]
+    api_url: Option<String>,
+
     /// The port to forward non-API frontend requests to
     #[clap(long, env = "GLANCE_WEB_PORT")]
     frontend_port: Option<u16>,
@@ -57,6 +78,16 @@ struct ServeCommand {
     #[clap(env = "GLANCE_ENABLE_SCHEDULED_TASKS", default_value_t = false)]
     enable_scheduled_tasks: bool,
 
+    /// Run apps with this file extension through an interpreter, given as `EXT=COMMAND`, such
+    /// as `ts=bun run`.
+    #[clap(
+        long = "interpreter",
+        env = "GLANCE_INTERPRETERS",
+        value_delimiter = ',',
+        value_parser = parse_interpreter
+    )]
+    interpreters: Vec<(String, Vec<String>)>,
+
     /// Request timeout, in seconds
     #[clap(long, env = "GLANCE_REQUEST_TIMEOUT", default_value_t = 60)]
     request_timeout: u64,
@@ -129,6 +160,22 @@ struct ServeCommand {
     obfuscate_errors: Option<bool>,
 }
 
+fn parse_interpreter(value: &str) -> Result<(String, Vec<String>), String> {
+    let (ext, command) = value
+        .split_once('=')
+        .ok_or_else(|| format!("Expected EXT=COMMAND, got {value}"))?;
+    let ext = ext.trim().trim_start_matches('.');
+    let command = command
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if ext.is_empty() || command.is_empty() {
+        return Err(format!("Expected EXT=COMMAND, got {value}"));
+    }
+
+    Ok((ext.to_string(), command))
+}
+
 async fn serve(cmd: ServeCommand) -> Result<(), Report<Error>> {
     error_stack::Report::set_color_mode(error_stack::fmt::ColorMode::None);
 
@@ -190,8 +237,14 @@ async fn serve(cmd: ServeCommand) -> Result<(), Report<Error>> {
 
     let platform = Platform::new(PlatformOptions {
         base_dir: cmd.base_dir,
+        watched_roots: cmd.watched_roots,
         db: pg_pool.clone(),
         enable_scheduled_tasks: cmd.enable_scheduled_tasks,
+        interpreters: cmd.interpreters.into_iter().collect(),
+        api_url: Some(
+            cmd.api_url
+                .unwrap_or_else(|| format!("http://localhost:{}/api", cmd.port)),
+        ),
     })
     .await?;
 
@@ -229,6 +282,7 @@ async fn serve(cmd: ServeCommand) -> Result<(), Report<Error>> {
         },
         db: platform.db.clone(),
         change_tx: platform.change_tx.clone(),
+        health: platform.health.clone(),
         secrets: server::Secrets::from_env()?,
     })
     .await?;
@@ -266,6 +320,8 @@ pub async fn actual_main() -> Result<(), Report<Error>> {
 
     match cli.command {
         Command::Db(cmd) => cmd.handle().await?,
+        Command::Apps(cmd) => cmd.handle().await?,
+        Command::Items(cmd) => cmd.handle().await?,
         Command::Serve(cmd) => serve(cmd).await?,
 
         Command::Util(cmd) => cmd.handle().await?,
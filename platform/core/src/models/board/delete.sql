@@ -0,0 +1,5 @@
+DELETE FROM public.boards
+WHERE id = $1
+  AND organization_id = $2
+  AND (user_id IS NULL
+    OR user_id = $3)
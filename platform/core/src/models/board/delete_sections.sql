@@ -0,0 +1,2 @@
+DELETE FROM public.board_sections
+WHERE board_id = $1
@@ -0,0 +1,363 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing,
+};
+use axum_jsonschema::Json;
+use error_stack::ResultExt;
+use filigree::extract::FormOrJson;
+
+use super::{
+    items::resolve_board_items, types::*, BoardId, CREATE_PERMISSION, READ_PERMISSION,
+    WRITE_PERMISSION,
+};
+use crate::{
+    auth::{has_any_permission, Authed},
+    db::AppVisibility,
+    server::ServerState,
+    Error,
+};
+
+async fn get(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(id): Path<BoardId>,
+) -> Result<impl IntoResponse, Error> {
+    let object = Board::get(&state.db, &auth, &id).await?;
+
+    Ok(Json(object))
+}
+
+async fn list(State(state): State<ServerState>, auth: Authed) -> Result<impl IntoResponse, Error> {
+    let results = Board::list(&state.db, &auth).await?;
+
+    Ok(Json(results))
+}
+
+async fn create(
+    State(state): State<ServerState>,
+    auth: Authed,
+    FormOrJson(payload): FormOrJson<BoardCreatePayload>,
+) -> Result<impl IntoResponse, Error> {
+    let mut tx = state.db.begin().await.change_context(Error::Db)?;
+    let result = Board::create(&mut *tx, &auth, payload).await?;
+    tx.commit().await.change_context(Error::Db)?;
+
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+async fn update(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(id): Path<BoardId>,
+    FormOrJson(payload): FormOrJson<BoardUpdatePayload>,
+) -> Result<impl IntoResponse, Error> {
+    let mut tx = state.db.begin().await.change_context(Error::Db)?;
+
+    let result = Board::update(&mut *tx, &auth, &id, payload).await?;
+
+    tx.commit().await.change_context(Error::Db)?;
+
+    if result {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+async fn delete(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(id): Path<BoardId>,
+) -> Result<impl IntoResponse, Error> {
+    let mut tx = state.db.begin().await.change_context(Error::Db)?;
+
+    let deleted = Board::delete(&mut *tx, &auth, &id).await?;
+
+    if !deleted {
+        return Ok(StatusCode::NOT_FOUND);
+    }
+
+    tx.commit().await.change_context(Error::Db)?;
+
+    Ok(StatusCode::OK)
+}
+
+async fn get_items(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(id): Path<BoardId>,
+) -> Result<impl IntoResponse, Error> {
+    let board = Board::get(&state.db, &auth, &id).await?;
+    let apps = state
+        .orm
+        .read_active_items(&AppVisibility::for_auth(Some(&*auth)))
+        .await?;
+
+    // Convert to a response here since the resolved items borrow from the board and apps.
+    Ok(Json(resolve_board_items(&board, &apps)).into_response())
+}
+
+pub fn create_routes() -> axum::Router<ServerState> {
+    axum::Router::new()
+        .route(
+            "/boards",
+            routing::get(list).route_layer(has_any_permission(vec![READ_PERMISSION, "org_admin"])),
+        )
+        .route(
+            "/boards/:id",
+            routing::get(get).route_layer(has_any_permission(vec![READ_PERMISSION, "org_admin"])),
+        )
+        .route(
+            "/boards/:id/items",
+            routing::get(get_items)
+                .route_layer(has_any_permission(vec![READ_PERMISSION, "org_admin"])),
+        )
+        .route(
+            "/boards",
+            routing::post(create)
+                .route_layer(has_any_permission(vec![CREATE_PERMISSION, "org_admin"])),
+        )
+        .route(
+            "/boards/:id",
+            routing::put(update)
+                .route_layer(has_any_permission(vec![WRITE_PERMISSION, "org_admin"])),
+        )
+        .route(
+            "/boards/:id",
+            routing::delete(delete)
+                .route_layer(has_any_permission(vec![CREATE_PERMISSION, "org_admin"])),
+        )
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::tests::{platform::app_data, start_app, BootstrappedData};
+
+    #[sqlx::test]
+    async fn board_crud(pool: sqlx::PgPool) {
+        let (_app, BootstrappedData { user, .. }) = start_app(pool).await;
+
+        let created = user
+            .client
+            .post("boards")
+            .json(&json!({
+                "name": "Morning",
+                "sections": [
+                    { "title": "News", "app_ids": ["news"], "size": "large" },
+                    { "columns": 2 }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+
+        assert_eq!(created["name"], "Morning");
+        assert_eq!(created["user_id"], user.user_id.to_string());
+        assert_eq!(created["sections"][0]["title"], "News");
+        assert_eq!(created["sections"][0]["size"], "large");
+        assert_eq!(created["sections"][1]["columns"], 2);
+        assert_eq!(created["sections"][1]["size"], "medium");
+
+        let id = created["id"].as_str().unwrap();
+
+        let response = user
+            .client
+            .put(&format!("boards/{id}"))
+            .json(&json!({
+                "name": "Evening",
+                "sections": [{ "title": "Everything" }]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let board = user
+            .client
+            .get(&format!("boards/{id}"))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(board["name"], "Evening");
+        assert_eq!(board["sections"].as_array().unwrap().len(), 1);
+        assert_eq!(board["sections"][0]["title"], "Everything");
+
+        let list = user
+            .client
+            .get("boards")
+            .send()
+            .await
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["id"], id);
+
+        let response = user
+            .client
+            .delete(&format!("boards/{id}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = user
+            .client
+            .get(&format!("boards/{id}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[sqlx::test]
+    async fn board_visibility(pool: sqlx::PgPool) {
+        let (
+            _app,
+            BootstrappedData {
+                admin_user,
+                user,
+                no_roles_user,
+                ..
+            },
+        ) = start_app(pool).await;
+
+        for (name, shared) in [("Personal", false), ("Shared", true)] {
+            admin_user
+                .client
+                .post("boards")
+                .json(&json!({ "name": name, "shared": shared }))
+                .send()
+                .await
+                .unwrap()
+                .error_for_status()
+                .unwrap();
+        }
+
+        let list = user
+            .client
+            .get("boards")
+            .send()
+            .await
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0]["name"], "Shared");
+        assert_eq!(list[0]["user_id"], serde_json::Value::Null);
+
+        let response = no_roles_user.client.get("boards").send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        // Only admins can delete a shared board.
+        let shared_id = list[0]["id"].as_str().unwrap();
+        let response = user
+            .client
+            .delete(&format!("boards/{shared_id}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+        let response = admin_user
+            .client
+            .delete(&format!("boards/{shared_id}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+    }
+
+    #[sqlx::test]
+    async fn board_items(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { user, .. }) = start_app(pool).await;
+
+        for (app_id, items) in [
+            (
+                "app-a",
+                json!([
+                    { "id": "a1", "data": { "title": "A1" }, "updated": "2024-01-01T00:00:00Z" },
+                    { "id": "a2", "data": { "title": "A2" }, "updated": "2024-01-01T00:00:00Z", "persistent": true }
+                ]),
+            ),
+            (
+                "app-b",
+                json!([
+                    { "id": "b1", "data": { "title": "B1" }, "updated": "2024-01-01T00:00:00Z" }
+                ]),
+            ),
+        ] {
+            let data = app_data(json!({ "name": app_id, "path": "/bin/test", "items": items }));
+            assert!(app.platform.send_app_data(app_id, data, false).await);
+        }
+
+        let created = user
+            .client
+            .post("boards")
+            .json(&json!({
+                "name": "Board",
+                "sections": [
+                    { "app_ids": ["app-b"] },
+                    { "persistent": true }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        let id = created["id"].as_str().unwrap();
+
+        let result = user
+            .client
+            .get(&format!("boards/{id}/items"))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+
+        assert_eq!(result["board"]["id"], id);
+
+        let sections = result["sections"].as_array().unwrap();
+        assert_eq!(sections.len(), 2);
+
+        let first = sections[0]["apps"].as_array().unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0]["app"]["id"], "app-b");
+        assert_eq!(first[0]["items"][0]["id"], "b1");
+
+        let second = sections[1]["apps"].as_array().unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0]["app"]["id"], "app-a");
+        let item_ids = second[0]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(item_ids, vec!["a2"]);
+    }
+}
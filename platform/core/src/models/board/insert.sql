@@ -0,0 +1,12 @@
+INSERT INTO public.boards (
+  id,
+  organization_id,
+  user_id,
+  name,
+  description)
+VALUES (
+  $1,
+  $2,
+  $3,
+  $4,
+  $5)
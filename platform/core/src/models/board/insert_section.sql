@@ -0,0 +1,20 @@
+INSERT INTO public.board_sections (
+  board_id,
+  position,
+  title,
+  app_ids,
+  include_dismissed,
+  persistent,
+  max_items,
+  columns,
+  size)
+VALUES (
+  $1,
+  $2,
+  $3,
+  $4,
+  $5,
+  $6,
+  $7,
+  $8,
+  $9)
@@ -0,0 +1,196 @@
+use serde::Serialize;
+
+use super::{Board, BoardSection};
+use crate::items::{AppInfo, AppItems, Item};
+
+/// The items to show on a board, arranged by section
+#[derive(Debug, Serialize)]
+pub struct BoardItems<'a> {
+    pub board: &'a Board,
+    pub sections: Vec<BoardSectionItems<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardSectionItems<'a> {
+    pub section: &'a BoardSection,
+    pub apps: Vec<BoardAppItems<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BoardAppItems<'a> {
+    pub app: &'a AppInfo,
+    pub items: Vec<&'a Item>,
+}
+
+impl BoardSection {
+    fn includes_item(&self, item: &Item) -> bool {
+        (self.include_dismissed || !item.dismissed)
+            && self.persistent.map_or(true, |p| p == item.persistent)
+    }
+
+    /// Select the apps and items that this section should show. Apps without any matching
+    /// items are left out.
+    pub fn select<'a>(&self, apps: &'a [AppItems]) -> Vec<BoardAppItems<'a>> {
+        let selected_apps: Box<dyn Iterator<Item = &AppItems>> = if self.app_ids.is_empty() {
+            Box::new(apps.iter())
+        } else {
+            Box::new(
+                self.app_ids
+                    .iter()
+                    .filter_map(|id| apps.iter().find(|app| &app.app.id == id)),
+            )
+        };
+
+        let max_items = self
+            .max_items
+            .map(|max| max.max(0) as usize)
+            .unwrap_or(usize::MAX);
+
+        selected_apps
+            .filter_map(|app| {
+                let items = app
+                    .items
+                    .iter()
+                    .filter(|item| self.includes_item(item))
+                    .take(max_items)
+                    .collect::<Vec<_>>();
+
+                (!items.is_empty()).then_some(BoardAppItems {
+                    app: &app.app,
+                    items,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Resolve the sections of a board against the current items.
+pub fn resolve_board_items<'a>(board: &'a Board, apps: &'a [AppItems]) -> BoardItems<'a> {
+    let sections = board
+        .sections
+        .iter()
+        .map(|section| BoardSectionItems {
+            section,
+            apps: section.select(apps),
+        })
+        .collect();
+
+    BoardItems { board, sections }
+}
+
+#[cfg(test)]
+mod tests {
+    use glance_app::AppItemData;
+
+    use super::*;
+
+    fn app(id: &str, items: Vec<Item>) -> AppItems {
+        AppItems {
+            app: AppInfo {
+                id: id.to_string(),
+                name: id.to_string(),
+                path: format!("/apps/{id}"),
+                error: None,
+                enabled: true,
+                validation_errors: None,
+            },
+            items,
+        }
+    }
+
+    fn item(app_id: &str, id: &str, persistent: bool, dismissed: bool) -> Item {
+        Item {
+            app_id: app_id.to_string(),
+            id: id.to_string(),
+            persistent,
+            state_key: None,
+            data: AppItemData {
+                title: id.to_string(),
+                subtitle: None,
+                detail: None,
+                url: None,
+                icon: None,
+                data: None,
+            },
+            notify: None,
+            updated_at: chrono::Utc::now(),
+            created_at: chrono::Utc::now(),
+            dismissed,
+        }
+    }
+
+    fn test_apps() -> Vec<AppItems> {
+        vec![
+            app(
+                "a",
+                vec![
+                    item("a", "a1", false, false),
+                    item("a", "a2", true, false),
+                    item("a", "a3", false, true),
+                ],
+            ),
+            app("b", vec![item("b", "b1", false, true)]),
+            app("c", vec![item("c", "c1", true, false)]),
+        ]
+    }
+
+    fn selected_ids(selected: &[BoardAppItems]) -> Vec<(String, Vec<String>)> {
+        selected
+            .iter()
+            .map(|app| {
+                (
+                    app.app.id.clone(),
+                    app.items.iter().map(|item| item.id.clone()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn default_section_shows_undismissed_items() {
+        let apps = test_apps();
+        let section = BoardSection::default();
+        assert_eq!(
+            selected_ids(&section.select(&apps)),
+            vec![
+                ("a".to_string(), vec!["a1".to_string(), "a2".to_string()]),
+                ("c".to_string(), vec!["c1".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn app_ids_select_and_order_apps() {
+        let apps = test_apps();
+        let section = BoardSection {
+            app_ids: vec!["c".to_string(), "missing".to_string(), "b".to_string()],
+            include_dismissed: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            selected_ids(&section.select(&apps)),
+            vec![
+                ("c".to_string(), vec!["c1".to_string()]),
+                ("b".to_string(), vec!["b1".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn item_filters() {
+        let apps = test_apps();
+        let section = BoardSection {
+            persistent: Some(false),
+            include_dismissed: true,
+            max_items: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            selected_ids(&section.select(&apps)),
+            vec![
+                ("a".to_string(), vec!["a1".to_string()]),
+                ("b".to_string(), vec!["b1".to_string()]),
+            ]
+        );
+    }
+}
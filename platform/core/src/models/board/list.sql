@@ -0,0 +1,16 @@
+SELECT
+  id AS "id: BoardId",
+  organization_id AS "organization_id: crate::models::organization::OrganizationId",
+  user_id AS "user_id: crate::models::user::UserId",
+  updated_at,
+  created_at,
+  name,
+  description
+FROM
+  public.boards tb
+WHERE
+  organization_id = $1
+  AND (user_id IS NULL
+    OR user_id = $2)
+ORDER BY
+  name
@@ -0,0 +1,19 @@
+//! Boards are user-defined dashboards which arrange apps and their items into sections.
+
+pub mod endpoints;
+pub mod items;
+pub mod queries;
+pub mod types;
+
+pub use types::*;
+
+pub const READ_PERMISSION: &str = "Board::read";
+pub const WRITE_PERMISSION: &str = "Board::write";
+pub const OWNER_PERMISSION: &str = "Board::owner";
+
+pub const CREATE_PERMISSION: &str = "Board::owner";
+
+/// Needed to delete a board that is shared with the whole organization
+pub const SHARED_BOARD_ADMIN_PERMISSION: &str = "org_admin";
+
+filigree::make_object_id!(BoardId, brd);
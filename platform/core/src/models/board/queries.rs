@@ -0,0 +1,184 @@
+use error_stack::ResultExt;
+use sqlx::{query_file, query_file_as, query_scalar, types::Json, PgConnection, PgExecutor};
+use tracing::instrument;
+
+use super::{types::*, BoardId};
+use crate::{auth::AuthInfo, Error};
+
+impl Board {
+    /// Get a Board from the database. Boards owned by other users are treated as missing.
+    #[instrument(skip(db))]
+    pub async fn get(
+        db: impl PgExecutor<'_>,
+        auth: &AuthInfo,
+        id: &BoardId,
+    ) -> Result<Board, error_stack::Report<Error>> {
+        auth.require_permission(super::READ_PERMISSION)?;
+
+        let object = query_file_as!(
+            Board,
+            "src/models/board/select_one.sql",
+            id.as_uuid(),
+            auth.organization_id.as_uuid(),
+            auth.user_id.as_uuid()
+        )
+        .fetch_optional(db)
+        .await
+        .change_context(Error::Db)?
+        .ok_or(Error::NotFound("Board"))?;
+
+        Ok(object)
+    }
+
+    /// List the boards shared with the organization and those owned by the current user.
+    #[instrument(skip(db))]
+    pub async fn list(
+        db: impl PgExecutor<'_>,
+        auth: &AuthInfo,
+    ) -> Result<Vec<BoardListResult>, error_stack::Report<Error>> {
+        auth.require_permission(super::READ_PERMISSION)?;
+
+        let results = query_file_as!(
+            BoardListResult,
+            "src/models/board/list.sql",
+            auth.organization_id.as_uuid(),
+            auth.user_id.as_uuid()
+        )
+        .fetch_all(db)
+        .await
+        .change_context(Error::Db)?;
+
+        Ok(results)
+    }
+
+    /// Create a new Board in the database.
+    #[instrument(skip(db))]
+    pub async fn create(
+        db: &mut PgConnection,
+        auth: &AuthInfo,
+        payload: BoardCreatePayload,
+    ) -> Result<Board, error_stack::Report<Error>> {
+        auth.require_permission(super::CREATE_PERMISSION)?;
+
+        let id = BoardId::new();
+        let user_id = (!payload.shared).then_some(auth.user_id);
+
+        query_file!(
+            "src/models/board/insert.sql",
+            id.as_uuid(),
+            auth.organization_id.as_uuid(),
+            user_id.as_ref().map(|id| id.as_uuid()),
+            &payload.name,
+            payload.description.as_deref()
+        )
+        .execute(&mut *db)
+        .await
+        .change_context(Error::Db)?;
+
+        Self::write_sections(&mut *db, &id, &payload.sections).await?;
+
+        Self::get(&mut *db, auth, &id).await
+    }
+
+    /// Update a Board and replace its sections. Returns false if the board was not found.
+    #[instrument(skip(db))]
+    pub async fn update(
+        db: &mut PgConnection,
+        auth: &AuthInfo,
+        id: &BoardId,
+        payload: BoardUpdatePayload,
+    ) -> Result<bool, error_stack::Report<Error>> {
+        auth.require_permission(super::WRITE_PERMISSION)?;
+
+        let result = query_file!(
+            "src/models/board/update.sql",
+            &payload.name,
+            payload.description.as_deref(),
+            id.as_uuid(),
+            auth.organization_id.as_uuid(),
+            auth.user_id.as_uuid()
+        )
+        .execute(&mut *db)
+        .await
+        .change_context(Error::Db)?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        query_file!("src/models/board/delete_sections.sql", id.as_uuid())
+            .execute(&mut *db)
+            .await
+            .change_context(Error::Db)?;
+
+        Self::write_sections(&mut *db, id, &payload.sections).await?;
+
+        Ok(true)
+    }
+
+    /// Delete a Board. Boards shared with the whole organization can only be deleted by an
+    /// organization admin. Returns false if the board was not found.
+    #[instrument(skip(db))]
+    pub async fn delete(
+        db: &mut PgConnection,
+        auth: &AuthInfo,
+        id: &BoardId,
+    ) -> Result<bool, error_stack::Report<Error>> {
+        auth.require_permission(super::CREATE_PERMISSION)?;
+
+        let shared = query_scalar!(
+            r##"SELECT user_id IS NULL AS "shared!"
+            FROM public.boards
+            WHERE id = $1 AND organization_id = $2 AND (user_id IS NULL OR user_id = $3)"##,
+            id.as_uuid(),
+            auth.organization_id.as_uuid(),
+            auth.user_id.as_uuid()
+        )
+        .fetch_optional(&mut *db)
+        .await
+        .change_context(Error::Db)?;
+
+        match shared {
+            None => return Ok(false),
+            Some(true) => auth.require_permission(super::SHARED_BOARD_ADMIN_PERMISSION)?,
+            Some(false) => {}
+        }
+
+        let result = query_file!(
+            "src/models/board/delete.sql",
+            id.as_uuid(),
+            auth.organization_id.as_uuid(),
+            auth.user_id.as_uuid()
+        )
+        .execute(&mut *db)
+        .await
+        .change_context(Error::Db)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn write_sections(
+        db: &mut PgConnection,
+        id: &BoardId,
+        sections: &[BoardSection],
+    ) -> Result<(), error_stack::Report<Error>> {
+        for (position, section) in sections.iter().enumerate() {
+            query_file!(
+                "src/models/board/insert_section.sql",
+                id.as_uuid(),
+                position as i32,
+                section.title.as_deref(),
+                &section.app_ids,
+                section.include_dismissed,
+                section.persistent,
+                section.max_items,
+                section.columns,
+                section.size as _
+            )
+            .execute(&mut *db)
+            .await
+            .change_context(Error::Db)?;
+        }
+
+        Ok(())
+    }
+}
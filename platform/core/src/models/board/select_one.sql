@@ -0,0 +1,24 @@
+SELECT
+  id AS "id: BoardId",
+  organization_id AS "organization_id: crate::models::organization::OrganizationId",
+  user_id AS "user_id: crate::models::user::UserId",
+  updated_at,
+  created_at,
+  name,
+  description,
+  COALESCE((
+    SELECT
+      jsonb_agg(jsonb_build_object('title', title, 'app_ids', app_ids,
+        'include_dismissed', include_dismissed, 'persistent', persistent,
+        'max_items', max_items, 'columns', columns, 'size', size)
+      ORDER BY position)
+    FROM board_sections
+    WHERE
+      board_id = tb.id), '[]'::jsonb) AS "sections!: Json<Vec<BoardSection>>"
+FROM
+  public.boards tb
+WHERE
+  id = $1
+  AND tb.organization_id = $2
+  AND (tb.user_id IS NULL
+    OR tb.user_id = $3)
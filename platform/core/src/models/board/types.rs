@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+
+use super::BoardId;
+use crate::models::{organization::OrganizationId, user::UserId};
+
+/// A hint for how much space a section's items should take up
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "board_section_size", rename_all = "snake_case")]
+pub enum BoardSectionSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+/// A section of a board, which selects the items to show and hints at how to lay them out.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardSection {
+    #[serde(default)]
+    pub title: Option<String>,
+    /// The apps to show in this section, in display order. If empty, all apps are shown.
+    #[serde(default)]
+    pub app_ids: Vec<String>,
+    /// Show items that have been dismissed
+    #[serde(default)]
+    pub include_dismissed: bool,
+    /// If set, only show items whose `persistent` flag matches this value.
+    #[serde(default)]
+    pub persistent: Option<bool>,
+    /// The maximum number of items to show from each app
+    #[serde(default)]
+    pub max_items: Option<i32>,
+    /// How many columns the section should span
+    #[serde(default)]
+    pub columns: Option<i32>,
+    #[serde(default)]
+    pub size: BoardSectionSize,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Board {
+    pub id: BoardId,
+    pub organization_id: OrganizationId,
+    /// The user who owns the board, or `None` if it is shared with the whole organization.
+    pub user_id: Option<UserId>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub description: Option<String>,
+    /// The board's sections, in display order
+    pub sections: Json<Vec<BoardSection>>,
+}
+
+/// A board as returned from the list endpoint, without its sections.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BoardListResult {
+    pub id: BoardId,
+    pub organization_id: OrganizationId,
+    pub user_id: Option<UserId>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+pub struct BoardCreatePayload {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// If true, the board is visible to everyone in the organization instead of only to the
+    /// user who created it.
+    #[serde(default)]
+    pub shared: bool,
+    #[serde(default)]
+    pub sections: Vec<BoardSection>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(test, derive(Serialize))]
+pub struct BoardUpdatePayload {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The new sections for the board, which replace all the existing sections.
+    #[serde(default)]
+    pub sections: Vec<BoardSection>,
+}
@@ -0,0 +1,11 @@
+UPDATE
+  public.boards
+SET
+  name = $1,
+  description = $2,
+  updated_at = NOW()
+WHERE
+  id = $3
+  AND organization_id = $4
+  AND (user_id IS NULL
+    OR user_id = $5)
@@ -1,3 +1,4 @@
+pub mod board;
 pub mod organization;
 pub mod role;
 pub mod user;
@@ -8,6 +9,7 @@ use crate::server::ServerState;
 
 pub fn create_routes() -> Router<ServerState> {
     Router::new()
+        .merge(board::endpoints::create_routes())
         .merge(role::endpoints::create_routes())
         .merge(user::endpoints::create_routes())
 }
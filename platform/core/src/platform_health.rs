@@ -0,0 +1,79 @@
+//! Track whether the platform's background workers are still running.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Shows whether a background worker is running. The worker holds the matching [WorkerGuard],
+/// which marks the worker as stopped when it exits or panics.
+#[derive(Clone, Debug)]
+pub struct WorkerStatus(Arc<AtomicBool>);
+
+impl WorkerStatus {
+    /// Create a status for a worker that is starting, and the guard for the worker to hold.
+    pub fn start() -> (Self, WorkerGuard) {
+        let running = Arc::new(AtomicBool::new(true));
+        (Self(running.clone()), WorkerGuard(running))
+    }
+
+    /// Return true if the worker has not stopped.
+    pub fn is_running(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Held by a background worker for as long as it runs
+#[derive(Debug)]
+pub struct WorkerGuard(Arc<AtomicBool>);
+
+impl WorkerGuard {
+    /// Mark the worker as running or not, for a worker that can stop working without exiting,
+    /// such as while it waits to restart.
+    pub fn set_running(&self, running: bool) {
+        self.0.store(running, Ordering::Relaxed);
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The status of the platform's background workers
+#[derive(Clone, Debug)]
+pub struct PlatformHealth {
+    /// The watcher for the app data directory, or `None` if the platform was built without it
+    pub fs_source: Option<WorkerStatus>,
+    /// The task that applies app data changes
+    pub change_handler: WorkerStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stopped_workers() {
+        let (finished, guard) = WorkerStatus::start();
+        tokio::task::spawn(async move {
+            let _guard = guard;
+        })
+        .await
+        .unwrap();
+        assert!(!finished.is_running());
+
+        let (panicked, guard) = WorkerStatus::start();
+        std::thread::spawn(move || {
+            let _guard = guard;
+            panic!("worker failed");
+        })
+        .join()
+        .expect_err("worker should panic");
+        assert!(!panicked.is_running());
+
+        let (running, _guard) = WorkerStatus::start();
+        assert!(running.is_running());
+    }
+}
@@ -0,0 +1,23 @@
+use serde::Serialize;
+use sqlx::types::Json;
+
+use crate::{validation::ValidationIssue, AppDataSource};
+
+/// An app data update that could not be applied, kept so that the app author can see exactly
+/// what was sent and replay it later.
+#[derive(Debug, Serialize)]
+pub struct RejectedPayload {
+    pub id: i64,
+    pub app_id: String,
+    /// Where the update came from
+    pub source: AppDataSource,
+    /// If the update was meant to be merged with the existing items
+    pub merge_items: bool,
+    /// The raw contents of the update
+    pub contents: String,
+    /// The error that caused the update to be rejected
+    pub error: String,
+    /// Located validation problems with the update, if that was the reason it was rejected
+    pub validation_errors: Option<Json<Vec<ValidationIssue>>>,
+    pub created_at: chrono::DateTime<chrono::offset::Utc>,
+}
@@ -1,36 +1,183 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use effectum::RunningJob;
 use error_stack::{Report, ResultExt};
-use glance_app::AppSchedule;
+use glance_app::{AppData, AppOutput, AppSchedule};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use tracing::{event, Level};
 
-use crate::{db::Db, error::Error};
+use crate::{
+    builtin_sources::{BuiltinSource, BuiltinSourceJobData, BuiltinSourceKind, BUILTIN_SOURCE_JOB},
+    db::Db,
+    error::Error,
+    handle_changes::record_rejected_change,
+    schedules::{ScheduleId, TIMEZONE_SCHEDULES_JOB},
+    validation, AppDataSource, AppFileContents, AppFileInput,
+};
+
+/// The task queue job which sends feedback on an app's items to the app's webhook
+pub const FEEDBACK_WEBHOOK_JOB: &str = "feedback-webhook";
+
+/// Map a file extension to the command that runs files with that extension
+pub type Interpreters = HashMap<String, Vec<String>>;
+
+/// How the app data says to start an app
+#[derive(Debug, Clone)]
+pub struct AppLaunch {
+    /// The path of the app
+    pub path: String,
+    /// The command that runs the app, if it's not run from `path` directly
+    pub command: Option<Json<Vec<String>>>,
+    /// The directory to run the app in
+    pub working_dir: Option<String>,
+    /// How the app returns its data
+    pub output: AppOutput,
+    /// The built-in source that publishes the app's items, if it has no executable
+    pub builtin_source: Option<Json<BuiltinSource>>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ScheduledJobData {
     pub app_id: String,
+    /// The path of the app
     pub command: String,
+    /// The command from the app data, if the app isn't run from its path directly
+    #[serde(default)]
+    pub app_command: Option<Vec<String>>,
+    /// The working directory from the app data
+    #[serde(default)]
+    pub working_dir: Option<String>,
+    /// How the app returns its data
+    #[serde(default)]
+    pub output: AppOutput,
+    /// The command source that turns the app's output into items, for apps that have one
+    #[serde(default)]
+    pub builtin_source: Option<BuiltinSource>,
+    /// The schedule that queued this run, if any
+    #[serde(default)]
+    pub schedule_id: Option<ScheduleId>,
     pub schedule: AppSchedule,
 }
 
+/// Feedback to post to an app's webhook
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FeedbackWebhookData {
+    pub app_id: String,
+    /// The app's webhook URL
+    pub url: String,
+    pub feedback: glance_app::Feedback,
+}
+
+/// The file holding the standard output from an app's most recent scheduled run
+pub fn stdout_log_path(log_dir: &Path, app_id: &str) -> PathBuf {
+    log_dir.join(format!("{app_id}.stdout.log"))
+}
+
+/// The file holding the standard error from an app's most recent scheduled run
+pub fn stderr_log_path(log_dir: &Path, app_id: &str) -> PathBuf {
+    log_dir.join(format!("{app_id}.stderr.log"))
+}
+
+/// The program, arguments, and working directory for a run of an app
+#[derive(Debug, PartialEq, Eq)]
+struct Invocation {
+    program: String,
+    args: Vec<String>,
+    working_dir: Option<PathBuf>,
+}
+
+impl ScheduledJobData {
+    /// Work out how to run the app. An explicit command on the schedule or the app takes
+    /// precedence, then an interpreter configured for the extension of the app's path, and
+    /// otherwise the path is run directly.
+    fn invocation(&self, interpreters: &Interpreters) -> Invocation {
+        let explicit = self
+            .schedule
+            .command
+            .as_ref()
+            .or(self.app_command.as_ref())
+            .filter(|command| !command.is_empty());
+
+        let mut command = match explicit {
+            Some(command) => command.clone(),
+            None => {
+                let interpreter = Path::new(&self.command)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .and_then(|ext| interpreters.get(ext));
+                interpreter
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .chain([self.command.clone()])
+                    .collect()
+            }
+        };
+
+        let program = command.remove(0);
+        command.extend(self.schedule.arguments.iter().cloned());
+
+        let working_dir = self
+            .schedule
+            .working_dir
+            .as_ref()
+            .or(self.working_dir.as_ref())
+            .map(PathBuf::from)
+            .or_else(|| {
+                Path::new(&self.command)
+                    .parent()
+                    .filter(|dir| !dir.as_os_str().is_empty())
+                    .map(Path::to_path_buf)
+            });
+
+        Invocation {
+            program,
+            args: command,
+            working_dir,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ScheduledJobContext {
     log_dir: PathBuf,
     db: Db,
+    interpreters: Interpreters,
+    api_url: Option<String>,
+    change_tx: flume::Sender<AppFileInput>,
+    http_client: reqwest::Client,
 }
 
 pub async fn create_scheduled_task_runner(
     db: Db,
     log_dir: PathBuf,
+    interpreters: Interpreters,
+    api_url: Option<String>,
+    change_tx: flume::Sender<AppFileInput>,
 ) -> Result<effectum::Worker, effectum::Error> {
     let schedule_runner = effectum::JobRunner::builder("scheduled-app", run_scheduled_app).build();
+    let timezone_runner =
+        effectum::JobRunner::builder(TIMEZONE_SCHEDULES_JOB, queue_timezone_schedules).build();
+    let feedback_runner =
+        effectum::JobRunner::builder(FEEDBACK_WEBHOOK_JOB, send_feedback_webhook).build();
+    let builtin_source_runner =
+        effectum::JobRunner::builder(BUILTIN_SOURCE_JOB, poll_builtin_source).build();
     effectum::Worker::builder(
         &db.task_queue,
         Arc::new(ScheduledJobContext {
             db: db.clone(),
             log_dir,
+            interpreters,
+            api_url,
+            change_tx,
+            http_client: reqwest::Client::new(),
         }),
     )
     .max_concurrency(
@@ -38,7 +185,12 @@ pub async fn create_scheduled_task_runner(
             .map(|n| n.get())
             .unwrap_or(4) as u16,
     )
-    .jobs([schedule_runner])
+    .jobs([
+        schedule_runner,
+        timezone_runner,
+        feedback_runner,
+        builtin_source_runner,
+    ])
     .build()
     .await
 }
@@ -48,44 +200,751 @@ async fn run_scheduled_app(
     context: Arc<ScheduledJobContext>,
 ) -> Result<(), Report<Error>> {
     let data: ScheduledJobData = job.json_payload().change_context(Error::ScheduledTask)?;
+    let start = Instant::now();
+    let result = execute_app(&job, &context, &data).await;
+    record_run_metrics(&data.app_id, start, &result);
+    result
+}
+
+/// Count a finished run and how long it took.
+fn record_run_metrics(app_id: &str, start: Instant, result: &Result<(), Report<Error>>) {
+    metrics::counter!("glance_scheduled_runs_total", "app_id" => app_id.to_string()).increment(1);
+    if result.is_err() {
+        metrics::counter!("glance_scheduled_run_failures_total", "app_id" => app_id.to_string())
+            .increment(1);
+    }
+    metrics::histogram!("glance_scheduled_run_duration_seconds", "app_id" => app_id.to_string())
+        .record(start.elapsed().as_secs_f64());
+}
 
-    event!(Level::INFO, cmd=%data.command, args=?data.schedule.arguments, "Running scheduled job");
+async fn execute_app(
+    job: &RunningJob,
+    context: &ScheduledJobContext,
+    data: &ScheduledJobData,
+) -> Result<(), Report<Error>> {
+    let invocation = data.invocation(&context.interpreters);
+    event!(Level::INFO, program=%invocation.program, args=?invocation.args, "Running scheduled job");
 
-    let stdout_fs_path = context.log_dir.join(format!("{}.stdout.log", data.app_id));
+    if let Some(schedule_id) = &data.schedule_id {
+        context
+            .db
+            .record_schedule_run(schedule_id)
+            .await
+            .change_context(Error::ScheduledTask)?;
+    }
+
+    let stdout_fs_path = stdout_log_path(&context.log_dir, &data.app_id);
     let stdout_fs = std::fs::File::create(&stdout_fs_path)
         .change_context(Error::ScheduledTask)
         .attach_printable_lazy(|| format!("Creating {}", stdout_fs_path.display()))?;
-    let stderr_fs_path = context.log_dir.join(format!("{}.stderr.log", data.app_id));
+    let stderr_fs_path = stderr_log_path(&context.log_dir, &data.app_id);
     let stderr_fs = std::fs::File::create(&stderr_fs_path)
         .change_context(Error::ScheduledTask)
         .attach_printable_lazy(|| format!("Creating {}", stderr_fs_path.display()))?;
 
-    let mut cmd = tokio::process::Command::new(&data.command);
-    cmd.args(&data.schedule.arguments)
-        .stdout(std::process::Stdio::from(stdout_fs))
+    let mut cmd = tokio::process::Command::new(&invocation.program);
+    cmd.args(&invocation.args)
         .stderr(std::process::Stdio::from(stderr_fs))
         .kill_on_drop(true);
 
-    if let Some(wd) = std::path::PathBuf::from(&data.command).parent() {
+    // When the app prints its data, capture it here and copy it to the log afterward.
+    let mut stdout_log = None;
+    match data.output {
+        AppOutput::File => {
+            cmd.stdout(std::process::Stdio::from(stdout_fs));
+        }
+        AppOutput::Stdout => {
+            cmd.stdout(std::process::Stdio::piped())
+                .env(glance_app::OUTPUT_ENV_VAR, "stdout");
+            stdout_log = Some(stdout_fs);
+        }
+    }
+
+    if let Some(wd) = &invocation.working_dir {
         cmd.current_dir(wd);
     };
 
-    let mut proc = cmd.spawn().change_context(Error::ScheduledTask)?;
+    // Let the app call back into the API, for example to fetch feedback.
+    if let Some(api_url) = &context.api_url {
+        cmd.env(glance_app::API_URL_ENV_VAR, api_url);
+    }
+
+    let settings = context
+        .db
+        .get_platform_app_settings(&data.app_id)
+        .await
+        .change_context(Error::ScheduledTask)?;
+    if let Some(settings) = settings {
+        cmd.env(glance_app::SETTINGS_ENV_VAR, settings.to_string());
+    }
+
+    // This is deleted when the run finishes.
+    let previous_items = write_previous_items(&context.db, &data.app_id).await?;
+    cmd.env(glance_app::PREVIOUS_ITEMS_ENV_VAR, previous_items.path());
+
+    let proc = cmd.spawn().change_context(Error::ScheduledTask)?;
 
     let timeout = job.expires.load(std::sync::atomic::Ordering::Relaxed);
     let timeout = chrono::DateTime::from_timestamp(timeout, 0).unwrap();
     let duration = timeout - chrono::Utc::now();
 
-    let res = tokio::time::timeout(duration.to_std().unwrap(), proc.wait())
+    let output = tokio::time::timeout(duration.to_std().unwrap(), proc.wait_with_output())
         .await
-        .map_err(|_| Error::ScheduledTask)
+        .map_err(|_| {
+            metrics::counter!("glance_scheduled_run_timeouts_total", "app_id" => data.app_id.clone())
+                .increment(1);
+            Error::ScheduledTask
+        })
         .attach_printable("Task timed out")?
         .change_context(Error::ScheduledTask)?;
 
-    if !res.success() {
-        return Err(Report::new(Error::ScheduledTask))
-            .attach_printable(format!("Command failed with {}", res));
+    if let Some(mut log) = stdout_log {
+        log.write_all(&output.stdout)
+            .change_context(Error::ScheduledTask)
+            .attach_printable_lazy(|| format!("Writing {}", stdout_fs_path.display()))?;
+    }
+
+    if !output.status.success() {
+        let e = Report::new(Error::ScheduledTask)
+            .attach_printable(format!("Command failed with {}", output.status));
+        // Apps without a built-in source report their own errors in their data, but a command
+        // source has nowhere else to show that the command failed.
+        if data.builtin_source.is_some() {
+            record_rejected_change(
+                &context.db,
+                &data.app_id,
+                AppDataSource::ScheduledRun,
+                false,
+                None,
+                &e,
+            )
+            .await;
+        }
+        return Err(e);
+    }
+
+    match (&data.builtin_source, data.output) {
+        (Some(source), _) => {
+            submit_command_output(context, &data.app_id, source, &output.stdout).await?
+        }
+        (None, AppOutput::Stdout) => {
+            submit_stdout_data(context, &data.app_id, &output.stdout).await?
+        }
+        (None, AppOutput::File) => {}
     }
 
     Ok(())
 }
+
+/// Write the app's current items to a temporary file, so that the app can see what it published
+/// before and which of those items the viewer has dismissed.
+async fn write_previous_items(
+    db: &Db,
+    app_id: &str,
+) -> Result<tempfile::NamedTempFile, Report<Error>> {
+    let items = db
+        .read_app_items(app_id)
+        .await
+        .change_context(Error::ScheduledTask)?
+        .into_iter()
+        .map(|item| item.into_previous_item())
+        .collect::<Vec<_>>();
+
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("glance-{app_id}-"))
+        .suffix(".json")
+        .tempfile()
+        .change_context(Error::ScheduledTask)
+        .attach_printable("Creating previous items file")?;
+    serde_json::to_writer(&mut file, &items)
+        .change_context(Error::ScheduledTask)
+        .attach_printable("Writing previous items file")?;
+
+    Ok(file)
+}
+
+/// Parse the app data that a run printed to standard output and send it to the change handler.
+/// Data that can't be parsed fails the run, and is saved as a rejected payload like any other.
+async fn submit_stdout_data(
+    context: &ScheduledJobContext,
+    app_id: &str,
+    stdout: &[u8],
+) -> Result<(), Report<Error>> {
+    let contents = String::from_utf8_lossy(stdout);
+    let data = match validation::parse_app_data(&contents) {
+        Ok(data) => data,
+        Err(e) => {
+            let e = e
+                .change_context(Error::ReadAppData)
+                .attach_printable(format!("App ID: {app_id}"));
+            record_rejected_change(
+                &context.db,
+                app_id,
+                AppDataSource::ScheduledRun,
+                false,
+                Some(&contents),
+                &e,
+            )
+            .await;
+            return Err(e.change_context(Error::ScheduledTask));
+        }
+    };
+
+    context
+        .change_tx
+        .send_async(AppFileInput {
+            app_id: app_id.to_string(),
+            contents: AppFileContents::Parsed(Box::new(data)),
+            merge_items: false,
+            source: AppDataSource::ScheduledRun,
+            owner: None,
+        })
+        .await
+        .map_err(|_| Report::new(Error::ScheduledTask))
+        .attach_printable("The change handler has shut down")
+}
+
+async fn poll_builtin_source(
+    job: RunningJob,
+    context: Arc<ScheduledJobContext>,
+) -> Result<(), Report<Error>> {
+    let data: BuiltinSourceJobData = job.json_payload().change_context(Error::ScheduledTask)?;
+    let start = Instant::now();
+    let result = run_builtin_source(&context, &data.app_id).await;
+    record_run_metrics(&data.app_id, start, &result);
+    result
+}
+
+/// Fetch the items from an app's built-in source and send them to the change handler as the
+/// app's full set of items. A failed fetch fails the run and leaves the existing items alone.
+async fn run_builtin_source(
+    context: &ScheduledJobContext,
+    app_id: &str,
+) -> Result<(), Report<Error>> {
+    let Some((mut app, source)) = context.db.get_builtin_source_app(app_id).await? else {
+        // The app was removed, disabled, or changed since the job was queued.
+        return Ok(());
+    };
+
+    event!(Level::INFO, %app_id, "Polling built-in source");
+    app.items = match source.fetch(&context.http_client).await {
+        Ok(items) => items,
+        Err(e) => {
+            let e = e.attach_printable(format!("App ID: {app_id}"));
+            record_rejected_change(
+                &context.db,
+                app_id,
+                AppDataSource::ScheduledRun,
+                false,
+                None,
+                &e,
+            )
+            .await;
+            return Err(e.change_context(Error::ScheduledTask));
+        }
+    };
+
+    send_source_items(context, app_id, app).await
+}
+
+/// Turn the output of a command source into the app's items and send them to the change
+/// handler. Output that can't be read fails the run, and is saved as a rejected payload.
+async fn submit_command_output(
+    context: &ScheduledJobContext,
+    app_id: &str,
+    source: &BuiltinSource,
+    stdout: &[u8],
+) -> Result<(), Report<Error>> {
+    let BuiltinSourceKind::Command(command) = &source.kind else {
+        return Err(Report::new(Error::ScheduledTask))
+            .attach_printable("Only command sources can read an app's output");
+    };
+
+    let Some((mut app, _)) = context.db.get_builtin_source_app(app_id).await? else {
+        return Ok(());
+    };
+
+    let contents = String::from_utf8_lossy(stdout);
+    app.items = match command.parse_output(&contents) {
+        Ok(items) => items,
+        Err(e) => {
+            let e = e.attach_printable(format!("App ID: {app_id}"));
+            record_rejected_change(
+                &context.db,
+                app_id,
+                AppDataSource::ScheduledRun,
+                false,
+                Some(&contents),
+                &e,
+            )
+            .await;
+            return Err(e.change_context(Error::ScheduledTask));
+        }
+    };
+
+    send_source_items(context, app_id, app).await
+}
+
+/// Send the items from a built-in source to the change handler as the app's full set of items.
+async fn send_source_items(
+    context: &ScheduledJobContext,
+    app_id: &str,
+    app: AppData,
+) -> Result<(), Report<Error>> {
+    context
+        .change_tx
+        .send_async(AppFileInput {
+            app_id: app_id.to_string(),
+            contents: AppFileContents::Parsed(Box::new(app)),
+            merge_items: false,
+            source: AppDataSource::ScheduledRun,
+            owner: None,
+        })
+        .await
+        .map_err(|_| Report::new(Error::ScheduledTask))
+        .attach_printable("The change handler has shut down")
+}
+
+async fn queue_timezone_schedules(
+    _job: RunningJob,
+    context: Arc<ScheduledJobContext>,
+) -> Result<(), Report<Error>> {
+    context.db.queue_due_schedules().await
+}
+
+/// Post feedback to an app's webhook. The task queue retries the delivery if the app doesn't
+/// accept it.
+async fn send_feedback_webhook(
+    job: RunningJob,
+    context: Arc<ScheduledJobContext>,
+) -> Result<(), Report<Error>> {
+    let data: FeedbackWebhookData = job.json_payload().change_context(Error::ScheduledTask)?;
+
+    context
+        .http_client
+        .post(&data.url)
+        .timeout(std::time::Duration::from_secs(30))
+        .json(&data.feedback)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .change_context(Error::ScheduledTask)
+        .attach_printable_lazy(|| {
+            format!("Sending feedback to {} for {}", data.url, data.app_id)
+        })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::get, Router};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        builtin_sources::{
+            builtin_source_job_id, CommandFormat, CommandItemId, CommandSource, FeedSource,
+        },
+        db::AppInstallData,
+        tests::{platform::TestPlatform, serve_stub},
+        Platform, PlatformOptions,
+    };
+
+    fn job(path: &str, app_command: Option<&[&str]>, schedule: AppSchedule) -> ScheduledJobData {
+        ScheduledJobData {
+            app_id: "app".to_string(),
+            command: path.to_string(),
+            app_command: app_command.map(|c| c.iter().map(|s| s.to_string()).collect()),
+            working_dir: None,
+            output: AppOutput::File,
+            builtin_source: None,
+            schedule_id: None,
+            schedule,
+        }
+    }
+
+    fn schedule(arguments: &[&str]) -> AppSchedule {
+        AppSchedule {
+            cron: "0 0 * * * *".to_string(),
+            arguments: arguments.iter().map(|s| s.to_string()).collect(),
+            timeout: None,
+            timezone: None,
+            command: None,
+            working_dir: None,
+        }
+    }
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn run_path_directly() {
+        let invocation =
+            job("/apps/weather/run", None, schedule(&["--fast"])).invocation(&Interpreters::new());
+        assert_eq!(
+            invocation,
+            Invocation {
+                program: "/apps/weather/run".to_string(),
+                args: strings(&["--fast"]),
+                working_dir: Some(PathBuf::from("/apps/weather")),
+            }
+        );
+    }
+
+    #[test]
+    fn interpreter_for_extension() {
+        let interpreters = Interpreters::from([("ts".to_string(), strings(&["bun", "run"]))]);
+        let invocation =
+            job("/apps/weather/index.ts", None, schedule(&["--fast"])).invocation(&interpreters);
+        assert_eq!(invocation.program, "bun");
+        assert_eq!(
+            invocation.args,
+            strings(&["run", "/apps/weather/index.ts", "--fast"])
+        );
+    }
+
+    #[test]
+    fn explicit_commands() {
+        let interpreters = Interpreters::from([("py".to_string(), strings(&["python3"]))]);
+
+        let mut data = job(
+            "/apps/weather/main.py",
+            Some(&["python3", "-m", "weather"]),
+            schedule(&["--fast"]),
+        );
+        data.working_dir = Some("/srv/weather".to_string());
+        let invocation = data.invocation(&interpreters);
+        assert_eq!(invocation.program, "python3");
+        assert_eq!(invocation.args, strings(&["-m", "weather", "--fast"]));
+        assert_eq!(invocation.working_dir, Some(PathBuf::from("/srv/weather")));
+
+        // The schedule's command and working directory take precedence over the app's.
+        data.schedule.command = Some(strings(&["docker", "run", "weather"]));
+        data.schedule.working_dir = Some("/tmp".to_string());
+        let invocation = data.invocation(&interpreters);
+        assert_eq!(invocation.program, "docker");
+        assert_eq!(invocation.args, strings(&["run", "weather", "--fast"]));
+        assert_eq!(invocation.working_dir, Some(PathBuf::from("/tmp")));
+    }
+
+    fn job_context(platform: &TestPlatform) -> ScheduledJobContext {
+        ScheduledJobContext {
+            log_dir: platform.base_dir.path().join(crate::LOG_SUBDIR),
+            db: platform.platform.db.clone(),
+            interpreters: Interpreters::new(),
+            api_url: None,
+            change_tx: platform.platform.change_tx.clone(),
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    fn printed_app(name: &str) -> String {
+        serde_json::json!({
+            "name": name,
+            "path": "/bin/printed",
+            "output": "stdout",
+            "items": [{
+                "id": "a",
+                "data": { "title": "A" },
+                "updated": "2024-01-01T00:00:00Z",
+            }],
+        })
+        .to_string()
+    }
+
+    #[sqlx::test]
+    async fn stdout_data(pool: PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+
+        submit_stdout_data(&context, "app", printed_app("Printed").as_bytes())
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("app").await);
+
+        let db = &context.db;
+        let app = db.get_apps(&["app".to_string()]).await.unwrap().remove(0);
+        assert_eq!(app.name, "Printed");
+        assert_eq!(db.read_app_items("app").await.unwrap().len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn stdout_data_keeps_output_mode(pool: PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+        let db = &context.db;
+        db.install_app(&AppInstallData {
+            id: "app".to_string(),
+            name: "Printed".to_string(),
+            path: "/bin/printed".to_string(),
+            command: None,
+            working_dir: None,
+            output: AppOutput::Stdout,
+            schedule: Vec::new(),
+            builtin_source: None,
+        })
+        .await
+        .unwrap();
+
+        // The printed data doesn't say how it was returned.
+        let printed = serde_json::json!({
+            "name": "Printed",
+            "path": "/bin/printed",
+            "items": [],
+        });
+        submit_stdout_data(&context, "app", printed.to_string().as_bytes())
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("app").await);
+
+        let launch = db.get_app_launch("app").await.unwrap().unwrap();
+        assert_eq!(launch.output, AppOutput::Stdout);
+    }
+
+    #[sqlx::test]
+    async fn invalid_stdout_data(pool: PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+
+        submit_stdout_data(&context, "app", printed_app("Printed").as_bytes())
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("app").await);
+
+        // Stray log output mixed into the data fails the run.
+        let output = format!("Fetching...\n{}", printed_app("Printed again"));
+        submit_stdout_data(&context, "app", output.as_bytes())
+            .await
+            .expect_err("parsing stdout");
+
+        let db = &context.db;
+        let app = db.get_apps(&["app".to_string()]).await.unwrap().remove(0);
+        assert_eq!(app.name, "Printed");
+        assert!(app.error.is_some());
+
+        let rejected = db.get_rejected_payloads("app").await.unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].source, AppDataSource::ScheduledRun);
+        assert_eq!(rejected[0].contents, output);
+    }
+
+    #[sqlx::test]
+    async fn previous_items(pool: PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+
+        submit_stdout_data(&context, "app", printed_app("Printed").as_bytes())
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("app").await);
+        context
+            .db
+            .set_item_dismissed("app", "a", true)
+            .await
+            .unwrap();
+
+        let file = write_previous_items(&context.db, "app").await.unwrap();
+        let contents = std::fs::read_to_string(file.path()).unwrap();
+        let items: Vec<glance_app::PreviousItem> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "a");
+        assert_eq!(items[0].data.title, "A");
+        assert!(items[0].dismissed);
+
+        let path = file.path().to_path_buf();
+        drop(file);
+        assert!(!path.exists());
+
+        // Apps that haven't published anything yet get an empty list.
+        let file = write_previous_items(&context.db, "new-app").await.unwrap();
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "[]");
+    }
+
+    fn feed_install(id: &str, url: String) -> AppInstallData {
+        AppInstallData {
+            id: id.to_string(),
+            name: "News".to_string(),
+            path: String::new(),
+            command: None,
+            working_dir: None,
+            output: AppOutput::File,
+            schedule: Vec::new(),
+            builtin_source: Some(BuiltinSource {
+                interval: 600,
+                kind: BuiltinSourceKind::Feed(FeedSource::new(vec![url])),
+            }),
+        }
+    }
+
+    #[sqlx::test]
+    async fn builtin_feed_source(pool: PgPool) {
+        let base = serve_stub(Router::new().route(
+            "/rss",
+            get(|| async {
+                r#"<rss version="2.0"><channel><title>News</title>
+                <item><guid>story-1</guid><title>A story</title></item>
+                </channel></rss>"#
+            }),
+        ))
+        .await;
+
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+        let db = &context.db;
+        db.install_app(&feed_install("news", format!("{base}/rss")))
+            .await
+            .unwrap();
+
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("news:")
+            .await
+            .unwrap();
+        assert_eq!(jobs, vec![builtin_source_job_id("news")]);
+
+        sqlx::query!(
+            "UPDATE apps SET feedback_webhook = 'http://localhost/feedback',
+                ui = '{\"icon\": \"news\"}' WHERE id = 'news'"
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        run_builtin_source(&context, "news").await.unwrap();
+        assert!(platform.wait_for_change("news").await);
+
+        // Publishing the items keeps the configuration that was set on the app.
+        let row = sqlx::query!("SELECT feedback_webhook, ui FROM apps WHERE id = 'news'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            row.feedback_webhook.as_deref(),
+            Some("http://localhost/feedback")
+        );
+        assert_eq!(row.ui, serde_json::json!({ "icon": "news" }));
+
+        let app = db.get_apps(&["news".to_string()]).await.unwrap().remove(0);
+        assert_eq!(app.name, "News");
+        assert_eq!(app.path, "builtin:feed");
+        let items = db.read_app_items("news").await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "story-1");
+        assert_eq!(items[0].state_key.as_deref(), Some("story-1"));
+
+        // Disabling the app removes the polling job.
+        db.set_app_enabled("news", false).await.unwrap();
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("news:")
+            .await
+            .unwrap();
+        assert!(jobs.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn failed_feed_records_error(pool: PgPool) {
+        let base = serve_stub(Router::new()).await;
+
+        let platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+        let db = &context.db;
+        db.install_app(&feed_install("news", format!("{base}/missing")))
+            .await
+            .unwrap();
+
+        run_builtin_source(&context, "news")
+            .await
+            .expect_err("fetching a missing feed");
+
+        let app = db.get_apps(&["news".to_string()]).await.unwrap().remove(0);
+        assert!(app.error.is_some());
+    }
+
+    #[sqlx::test]
+    async fn command_source(pool: PgPool) {
+        let mut platform = TestPlatform::new(pool).await;
+        let context = job_context(&platform);
+        let db = &context.db;
+
+        let source = BuiltinSource {
+            interval: 0,
+            kind: BuiltinSourceKind::Command(CommandSource {
+                command: "systemctl --failed --plain --no-legend".to_string(),
+                format: CommandFormat::Lines,
+                id: CommandItemId::Title,
+            }),
+        };
+        let mut install = feed_install("failed", String::new());
+        install.name = "Failed units".to_string();
+        install.builtin_source = Some(source.clone());
+        install.schedule = vec![AppSchedule {
+            cron: "0 */5 * * * *".to_string(),
+            arguments: Vec::new(),
+            timeout: None,
+            timezone: None,
+            command: None,
+            working_dir: None,
+        }];
+        db.install_app(&install).await.unwrap();
+
+        // The command runs on the app's schedule instead of being polled.
+        let launch = db.get_app_launch("failed").await.unwrap().unwrap();
+        assert_eq!(launch.path, "builtin:command");
+        assert_eq!(launch.output, AppOutput::Stdout);
+        assert_eq!(
+            launch.command.unwrap().0,
+            vec!["sh", "-c", "systemctl --failed --plain --no-legend"]
+        );
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("failed:")
+            .await
+            .unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_ne!(jobs[0], builtin_source_job_id("failed"));
+
+        let output = "nginx.service loaded failed failed\nsshd.service loaded failed failed\n";
+        submit_command_output(&context, "failed", &source, output.as_bytes())
+            .await
+            .unwrap();
+        assert!(platform.wait_for_change("failed").await);
+
+        let app = db
+            .get_apps(&["failed".to_string()])
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(app.name, "Failed units");
+        let items = db.read_app_items("failed").await.unwrap();
+        assert_eq!(items.len(), 2);
+
+        // The submitted data keeps the command that runs the source.
+        let launch = db.get_app_launch("failed").await.unwrap().unwrap();
+        assert_eq!(launch.output, AppOutput::Stdout);
+        assert!(launch.command.is_some());
+    }
+
+    #[sqlx::test]
+    async fn shutdown_with_task_runner(pool: PgPool) {
+        let base_dir = tempfile::tempdir().unwrap();
+        let platform = Platform::new(PlatformOptions {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            watched_roots: Vec::new(),
+            db: pool,
+            enable_scheduled_tasks: true,
+            interpreters: Default::default(),
+            api_url: None,
+        })
+        .await
+        .unwrap();
+
+        // The runner holds its own sender for changes, which must not keep the change handler
+        // waiting.
+        tokio::time::timeout(std::time::Duration::from_secs(30), platform.shutdown())
+            .await
+            .expect("shutting down");
+    }
+}
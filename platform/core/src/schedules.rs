@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use glance_app::AppSchedule;
+use serde::Serialize;
+use sqlx::types::Json;
+
+filigree::make_object_id!(ScheduleId, sch);
+
+/// The ID of the recurring job which queues runs for schedules that have a timezone. The task
+/// queue evaluates cron specs in UTC, so these schedules are checked once a minute instead.
+pub const TIMEZONE_SCHEDULES_JOB: &str = "timezone-schedules";
+
+/// How many upcoming runs to show for each schedule by default
+pub const DEFAULT_UPCOMING_RUNS: usize = 5;
+
+/// A schedule on which the platform runs an app
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    /// The ID of the schedule, which stays the same as long as the app keeps a schedule with
+    /// the same cron spec and arguments.
+    pub id: ScheduleId,
+    /// The app to run
+    pub app_id: String,
+    /// The cron spec for the schedule
+    pub cron: String,
+    /// Arguments to pass to the app
+    pub arguments: Json<Vec<String>>,
+    /// How long to let each run take, in seconds
+    pub timeout: Option<i32>,
+    /// The timezone in which to evaluate the cron spec, or UTC if not set
+    pub timezone: Option<String>,
+    /// When the schedule last started a run of the app
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// The command to run in place of the app's command
+    pub command: Option<Json<Vec<String>>>,
+    /// The directory to run the app in, in place of the app's working directory
+    pub working_dir: Option<String>,
+}
+
+/// A schedule along with its upcoming run times
+#[derive(Debug, Serialize)]
+pub struct ScheduleStatus {
+    /// The schedule
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// The next times that the schedule will run
+    pub next_runs: Vec<DateTime<Utc>>,
+}
+
+impl Schedule {
+    /// The ID of the recurring job for this schedule in the task queue
+    pub fn job_id(&self) -> String {
+        format!("{}:{}", self.app_id, self.id)
+    }
+
+    /// The schedule in the form that the app declared it
+    pub fn app_schedule(&self) -> AppSchedule {
+        AppSchedule {
+            cron: self.cron.clone(),
+            arguments: self.arguments.0.clone(),
+            timeout: self.timeout.map(|t| t.max(0) as u32),
+            timezone: self.timezone.clone(),
+            command: self.command.as_ref().map(|c| c.0.clone()),
+            working_dir: self.working_dir.clone(),
+        }
+    }
+
+    /// The next `count` times after `after` that the schedule will run.
+    pub fn upcoming(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        upcoming_runs(&self.cron, self.timezone.as_deref(), after, count)
+    }
+
+    /// Add the upcoming run times to the schedule.
+    pub fn status(self, count: usize) -> ScheduleStatus {
+        let next_runs = self.upcoming(Utc::now(), count);
+        ScheduleStatus {
+            schedule: self,
+            next_runs,
+        }
+    }
+}
+
+/// The next `count` times after `after` that a cron spec matches, evaluated in `timezone`.
+/// Invalid specs and timezones never match, since they are rejected when the app data is
+/// validated.
+pub fn upcoming_runs(
+    cron: &str,
+    timezone: Option<&str>,
+    after: DateTime<Utc>,
+    count: usize,
+) -> Vec<DateTime<Utc>> {
+    let Ok(schedule) = cron::Schedule::from_str(cron) else {
+        return Vec::new();
+    };
+
+    match timezone {
+        None => schedule.after(&after).take(count).collect(),
+        Some(timezone) => {
+            let Ok(tz) = chrono_tz::Tz::from_str(timezone) else {
+                return Vec::new();
+            };
+
+            schedule
+                .after(&after.with_timezone(&tz))
+                .take(count)
+                .map(|time| time.with_timezone(&Utc))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn utc_runs() {
+        let runs = upcoming_runs("0 30 9 * * *", None, time("2024-01-15T10:00:00Z"), 2);
+        assert_eq!(
+            runs,
+            vec![time("2024-01-16T09:30:00Z"), time("2024-01-17T09:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn timezone_runs_follow_daylight_saving() {
+        let tz = Some("America/New_York");
+        let runs = upcoming_runs("0 0 9 * * *", tz, time("2024-01-15T00:00:00Z"), 1);
+        assert_eq!(runs, vec![time("2024-01-15T14:00:00Z")]);
+
+        let runs = upcoming_runs("0 0 9 * * *", tz, time("2024-07-15T00:00:00Z"), 1);
+        assert_eq!(runs, vec![time("2024-07-15T13:00:00Z")]);
+    }
+
+    #[test]
+    fn invalid_schedules_never_run() {
+        let after = time("2024-01-15T00:00:00Z");
+        assert!(upcoming_runs("not cron", None, after, 3).is_empty());
+        assert!(upcoming_runs("0 0 9 * * *", Some("Mars/Olympus"), after, 3).is_empty());
+    }
+}
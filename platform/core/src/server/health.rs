@@ -1,6 +1,116 @@
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
 use serde_json::json;
 
+use super::ServerState;
+use crate::{platform_health::WorkerStatus, schedules::TIMEZONE_SCHEDULES_JOB};
+
+/// How long to wait for a dependency to respond before considering it unhealthy
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub async fn healthz() -> impl IntoResponse {
     (StatusCode::OK, Json(json!({ "status": "ok" })))
 }
+
+/// The result of checking one part of the platform
+#[derive(Serialize)]
+struct Check {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Check {
+    fn ok() -> Self {
+        Self {
+            status: "ok",
+            error: None,
+        }
+    }
+
+    fn failed(error: impl ToString) -> Self {
+        Self {
+            status: "unhealthy",
+            error: Some(error.to_string()),
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        self.status != "unhealthy"
+    }
+
+    /// Check a dependency, failing if it takes too long to respond.
+    async fn run<E: std::fmt::Debug>(
+        check: impl std::future::Future<Output = Result<(), E>>,
+    ) -> Self {
+        match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+            Ok(Ok(())) => Self::ok(),
+            Ok(Err(e)) => Self::failed(format!("{e:?}")),
+            Err(_) => Self::failed("Timed out"),
+        }
+    }
+
+    fn worker(status: Option<&WorkerStatus>) -> Self {
+        match status {
+            Some(status) if status.is_running() => Self::ok(),
+            Some(_) => Self::failed("Stopped"),
+            None => Self {
+                status: "disabled",
+                error: None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ReadyChecks {
+    database: Check,
+    task_queue: Check,
+    fs_source: Check,
+    change_handler: Check,
+}
+
+/// Check that the server can do its work: the database and task queue respond, and the
+/// platform's background workers are running. Returns 503 if anything is unhealthy.
+pub async fn ready(State(state): State<ServerState>) -> impl IntoResponse {
+    let database =
+        Check::run(async { sqlx::query("SELECT 1").execute(&state.db).await.map(|_| ()) });
+    let task_queue = Check::run(async {
+        state
+            .orm
+            .task_queue
+            .list_recurring_jobs_with_prefix(TIMEZONE_SCHEDULES_JOB)
+            .await
+            .map(|_| ())
+    });
+    let (database, task_queue) = tokio::join!(database, task_queue);
+
+    // The change handler closes its receiver when it exits, so a disconnected channel also means
+    // that it has stopped.
+    let change_handler = if state.change_tx.is_disconnected() {
+        Check::failed("Stopped")
+    } else {
+        Check::worker(Some(&state.health.change_handler))
+    };
+
+    let checks = ReadyChecks {
+        database,
+        task_queue,
+        fs_source: Check::worker(state.health.fs_source.as_ref()),
+        change_handler,
+    };
+
+    let healthy = checks.database.is_ok()
+        && checks.task_queue.is_ok()
+        && checks.fs_source.is_ok()
+        && checks.change_handler.is_ok();
+    let (code, status) = if healthy {
+        (StatusCode::OK, "ok")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    };
+
+    (code, Json(json!({ "status": status, "checks": checks })))
+}
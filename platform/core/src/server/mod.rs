@@ -28,10 +28,11 @@ use tower_http::{
 };
 use tracing::{event, Level, Span};
 
-use crate::{db::Db, error::Error, AppFileInput};
+use crate::{db::Db, error::Error, platform_health::PlatformHealth, AppFileInput};
 
 mod health;
 mod meta;
+mod prometheus;
 mod routes;
 #[cfg(test)]
 mod tests;
@@ -53,6 +54,8 @@ pub struct ServerStateInner {
     pub secrets: Secrets,
     /// Send app changes as they arrive
     pub change_tx: flume::Sender<AppFileInput>,
+    /// Whether the platform's background workers are running
+    pub health: PlatformHealth,
 }
 
 impl ServerStateInner {
@@ -209,6 +212,8 @@ pub struct Config {
     pub request_timeout: std::time::Duration,
     pub change_tx: flume::Sender<AppFileInput>,
     pub db: Db,
+    /// Whether the platform's background workers are running, for the readiness check
+    pub health: PlatformHealth,
 
     pub cookie_configuration: SessionCookieBuilder,
     /// When user sessions should expire.
@@ -241,6 +246,8 @@ pub struct Config {
 /// Create the server and return it, ready to run.
 pub async fn create_server(config: Config) -> Result<Server, Report<Error>> {
     let production = config.env != "development" && !cfg!(debug_assertions);
+    // Install the metrics recorder before anything records metrics.
+    prometheus::prometheus_handle();
     let obfuscate_errors = config.obfuscate_errors.unwrap_or(production);
 
     let host_values = config
@@ -286,6 +293,7 @@ pub async fn create_server(config: Config) -> Result<Server, Report<Error>> {
         db: pg_pool.clone(),
         orm: config.db,
         change_tx: config.change_tx,
+        health: config.health,
         secrets: config.secrets,
     }));
 
@@ -305,6 +313,7 @@ pub async fn create_server(config: Config) -> Result<Server, Report<Error>> {
 
     let api_routes: Router<ServerState> = Router::new()
         .route("/healthz", get(health::healthz))
+        .route("/healthz/ready", get(health::ready))
         .nest("/meta", meta::create_routes())
         .merge(filigree::auth::endpoints::create_routes())
         .merge(filigree::auth::oauth::create_routes())
@@ -313,10 +322,14 @@ pub async fn create_server(config: Config) -> Result<Server, Report<Error>> {
         .merge(crate::auth::create_routes())
         .merge(routes::items::routes())
         .merge(routes::app::routes())
+        .merge(routes::rejected::routes())
+        .merge(routes::settings::routes())
         // Return not found here so we don't run the other non-API fallbacks
         .fallback(|| async { Error::NotFound("Route") });
 
-    let app = Router::new().nest("/api", api_routes);
+    let app = Router::new()
+        .nest("/api", api_routes)
+        .route("/metrics", get(prometheus::metrics));
 
     let ServeFrontend {
         port: mut web_port,
@@ -417,6 +430,7 @@ pub async fn create_server(config: Config) -> Result<Server, Report<Error>> {
                     .on_request(DefaultOnRequest::new().level(Level::INFO))
                     .on_failure(DefaultOnFailure::new().level(Level::ERROR)),
             )
+            .layer(axum::middleware::from_fn(prometheus::record_http_metrics))
             .layer(TimeoutLayer::new(config.request_timeout))
             .layer(api_cors_layer)
             .layer(tower_cookies::CookieManagerLayer::new())
@@ -0,0 +1,100 @@
+//! Prometheus metrics for the server and platform
+
+use std::{sync::OnceLock, time::Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use error_stack::Report;
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use super::ServerState;
+use crate::Error;
+
+/// Histogram buckets for durations, in seconds. These cover quick requests up to scheduled runs
+/// that hit the default five minute timeout.
+const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+fn builder() -> PrometheusBuilder {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), DURATION_BUCKETS)
+        .expect("duration buckets are not empty")
+}
+
+/// The handle to the global metrics recorder, installing the recorder the first time this is
+/// called.
+pub fn prometheus_handle() -> &'static PrometheusHandle {
+    static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+    HANDLE.get_or_init(|| {
+        builder().install_recorder().unwrap_or_else(|e| {
+            // Another recorder is already installed, so metrics go there and this one stays
+            // empty.
+            tracing::warn!(error = %e, "Could not install the Prometheus metrics recorder");
+            builder().build_recorder().handle()
+        })
+    })
+}
+
+/// Count requests and their latency by route. Requests that didn't match a route are grouped
+/// together, so that arbitrary URLs don't each create a new series.
+pub async fn record_http_metrics(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let start = Instant::now();
+    let response = next.run(req).await;
+    let latency = start.elapsed();
+
+    let status = response.status().as_u16().to_string();
+    metrics::counter!("glance_http_requests_total",
+        "method" => method.clone(), "route" => route.clone(), "status" => status)
+    .increment(1);
+    metrics::histogram!("glance_http_request_duration_seconds",
+        "method" => method, "route" => route)
+    .record(latency.as_secs_f64());
+
+    response
+}
+
+/// Update the metrics that are read from the database, since they don't change in response to
+/// any one event.
+async fn record_db_metrics(state: &ServerState) -> Result<(), Report<Error>> {
+    let pool = &state.db;
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    metrics::gauge!("glance_db_pool_connections", "state" => "idle").set(idle as f64);
+    metrics::gauge!("glance_db_pool_connections", "state" => "in_use")
+        .set(size.saturating_sub(idle) as f64);
+    metrics::gauge!("glance_db_pool_max_connections")
+        .set(pool.options().get_max_connections() as f64);
+
+    for counts in state.orm.item_counts().await? {
+        metrics::gauge!("glance_items", "app_id" => counts.app_id.clone(), "state" => "active")
+            .set(counts.active as f64);
+        metrics::gauge!("glance_items", "app_id" => counts.app_id, "state" => "dismissed")
+            .set(counts.dismissed as f64);
+    }
+
+    Ok(())
+}
+
+/// Render the metrics in the Prometheus text format.
+pub async fn metrics(State(state): State<ServerState>) -> Result<impl IntoResponse, Error> {
+    let handle = prometheus_handle();
+    record_db_metrics(&state).await?;
+    handle.run_upkeep();
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        handle.render(),
+    ))
+}
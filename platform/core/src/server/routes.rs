@@ -1,4 +1,40 @@
 pub mod app;
 pub mod items;
+pub mod rejected;
+pub mod settings;
+
+use std::time::Duration;
 
 pub(super) use super::ServerState;
+use crate::{auth::AuthInfo, db::AppVisibility, error::Error, items::AppInfo, AppFileInput};
+
+/// How long to wait for room in the change queue before giving up
+const CHANGE_QUEUE_SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Send an app update to the change handler, returning an error if the queue stays full or the
+/// change handler has stopped.
+pub(super) async fn queue_change(state: &ServerState, input: AppFileInput) -> Result<(), Error> {
+    match tokio::time::timeout(CHANGE_QUEUE_SEND_TIMEOUT, state.change_tx.send_async(input)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(_)) => Err(Error::ChangeQueueUnavailable(
+            "change handler is not running",
+        )),
+        Err(_) => Err(Error::ChangeQueueUnavailable("too many pending updates")),
+    }
+}
+
+/// Read an app, returning [Error::NotFound] unless it exists and the sender of the request can
+/// see it.
+pub(super) async fn require_visible_app(
+    state: &ServerState,
+    auth: Option<&AuthInfo>,
+    app_id: &str,
+) -> Result<AppInfo, Error> {
+    let app = state
+        .orm
+        .get_visible_apps(&[app_id.to_string()], &AppVisibility::for_auth(auth))
+        .await?
+        .pop()
+        .ok_or(Error::NotFound("App"))?;
+    Ok(app)
+}
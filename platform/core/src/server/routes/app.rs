@@ -1,28 +1,169 @@
 use axum::{
     extract::{Path, State},
     response::IntoResponse,
-    routing::{get, put},
+    routing::{get, post, put},
     Json, Router,
 };
 use axum_extra::extract::Query;
+use error_stack::ResultExt;
 use glance_app::AppData;
 use http::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use super::ServerState;
-use crate::{error::Error, AppFileContents, AppFileInput};
+use crate::{
+    auth::{has_any_permission, Authed},
+    db::{AppInstallData, AppVisibility},
+    error::Error,
+    items::AppInfo,
+    schedules::{ScheduleStatus, DEFAULT_UPCOMING_RUNS},
+    validation, AppDataSource, AppFileContents, AppFileInput,
+};
+
+/// The most upcoming runs that can be requested for each schedule
+const MAX_UPCOMING_RUNS: usize = 100;
+
+/// The most feedback entries returned from one request
+const MAX_FEEDBACK: i64 = 1000;
+
+async fn list_apps(
+    State(state): State<ServerState>,
+    auth: Option<Authed>,
+) -> Result<impl IntoResponse, Error> {
+    let apps = state
+        .orm
+        .list_apps(&AppVisibility::for_auth(auth.as_deref()))
+        .await?;
+    Ok(Json(apps))
+}
+
+async fn install_app(
+    State(state): State<ServerState>,
+    Json(install): Json<AppInstallData>,
+) -> Result<impl IntoResponse, Error> {
+    let app = state.orm.install_app(&install).await?;
+    Ok((StatusCode::CREATED, Json(app)))
+}
+
+async fn set_enabled(
+    state: &ServerState,
+    app_id: &str,
+    enabled: bool,
+) -> Result<StatusCode, Error> {
+    if state.orm.set_app_enabled(app_id, enabled).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+async fn enable_app(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+) -> Result<impl IntoResponse, Error> {
+    set_enabled(&state, &app_id, true).await
+}
+
+async fn disable_app(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+) -> Result<impl IntoResponse, Error> {
+    set_enabled(&state, &app_id, false).await
+}
+
+async fn uninstall_app(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+) -> Result<impl IntoResponse, Error> {
+    if state.orm.uninstall_app(&app_id).await? {
+        Ok(StatusCode::OK)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleQuery {
+    /// How many upcoming runs to list for each schedule
+    runs: Option<usize>,
+}
+
+/// An app's status along with its schedules
+#[derive(Debug, Serialize)]
+struct AppStatus {
+    #[serde(flatten)]
+    app: AppInfo,
+    schedules: Vec<ScheduleStatus>,
+}
+
+async fn read_schedule_statuses(
+    state: &ServerState,
+    app_id: &str,
+    query: &ScheduleQuery,
+) -> Result<Vec<ScheduleStatus>, Error> {
+    let runs = query
+        .runs
+        .unwrap_or(DEFAULT_UPCOMING_RUNS)
+        .min(MAX_UPCOMING_RUNS);
+    let schedules = state.orm.get_app_schedules(&state.db, app_id).await?;
+    Ok(schedules.into_iter().map(|s| s.status(runs)).collect())
+}
 
 async fn get_app(
     Path(app_id): Path<String>,
     State(state): State<ServerState>,
+    auth: Option<Authed>,
+    Query(query): Query<ScheduleQuery>,
 ) -> Result<impl IntoResponse, Error> {
     let app = state
         .orm
-        .get_apps(&[app_id])
+        .get_visible_apps(&[app_id], &AppVisibility::for_auth(auth.as_deref()))
         .await?
         .pop()
         .ok_or_else(|| Error::NotFound("App"))?;
-    Ok(Json(app))
+    let schedules = read_schedule_statuses(&state, &app.id, &query).await?;
+    Ok(Json(AppStatus { app, schedules }))
+}
+
+async fn get_app_schedules(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+    auth: Option<Authed>,
+    Query(query): Query<ScheduleQuery>,
+) -> Result<impl IntoResponse, Error> {
+    let visibility = AppVisibility::for_auth(auth.as_deref());
+    if state
+        .orm
+        .get_visible_apps(&[app_id.clone()], &visibility)
+        .await?
+        .is_empty()
+    {
+        return Err(Error::NotFound("App"));
+    }
+
+    let schedules = read_schedule_statuses(&state, &app_id, &query).await?;
+    Ok(Json(schedules))
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedbackQuery {
+    /// Only return feedback with an ID greater than this one. The ID is used instead of a time
+    /// because events can commit in a different order than their timestamps.
+    since_id: Option<i64>,
+}
+
+async fn get_app_feedback(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+    auth: Option<Authed>,
+    Query(query): Query<FeedbackQuery>,
+) -> Result<impl IntoResponse, Error> {
+    super::require_visible_app(&state, auth.as_deref(), &app_id).await?;
+    let feedback = state
+        .orm
+        .get_app_feedback(&app_id, query.since_id, MAX_FEEDBACK)
+        .await?;
+    Ok(Json(feedback))
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,19 +177,688 @@ async fn update_app(
     Query(query): Query<UpdateQuery>,
     Json(app): Json<Box<AppData>>,
 ) -> Result<impl IntoResponse, Error> {
+    // Validate up front so that the submitter sees the problems directly.
+    validation::validate_app_data(&app).change_context(Error::InvalidAppData)?;
+
     let app_data = AppFileInput {
         app_id,
         contents: AppFileContents::Parsed(app),
         merge_items: query.merge.unwrap_or(false),
+        source: AppDataSource::Http,
+        owner: None,
     };
 
-    state.change_tx.send_async(app_data).await.ok();
+    super::queue_change(&state, app_data).await?;
     Ok(StatusCode::ACCEPTED)
 }
 
 /// Create the app routes
 pub fn routes() -> Router<ServerState> {
     Router::new()
+        .route("/apps", get(list_apps))
         .route("/apps/:app_id", get(get_app))
-        .route("/apps/:app_id", put(update_app))
+        .route("/apps/:app_id/schedules", get(get_app_schedules))
+        .route("/apps/:app_id/feedback", get(get_app_feedback))
+        // Managing apps controls which commands the server runs, so it is limited to admins.
+        // Publishing app data can also set the app's command and schedule, so it is included.
+        .route(
+            "/apps/:app_id",
+            put(update_app).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route(
+            "/apps",
+            post(install_app).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route(
+            "/apps/:app_id",
+            axum::routing::delete(uninstall_app).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route(
+            "/apps/:app_id/enable",
+            post(enable_app).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route(
+            "/apps/:app_id/disable",
+            post(disable_app).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use glance_app::AppOutput;
+    use serde_json::json;
+
+    use crate::{
+        db::{AppInstallData, AppVisibility},
+        error::Error,
+        handle_changes::MAX_WAITING,
+        tests::{start_app, BootstrappedData},
+        AppDataSource, AppFileContents, AppFileInput, AppOwner, CHANGE_QUEUE_SIZE,
+    };
+
+    fn app_json(items: serde_json::Value) -> serde_json::Value {
+        json!({
+            "name": "Route App",
+            "path": "/bin/test",
+            "items": items,
+        })
+    }
+
+    #[sqlx::test]
+    async fn full_change_queue(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { admin_user, .. }) = start_app(pool.clone()).await;
+
+        let response = admin_user
+            .client
+            .put("apps/slow")
+            .json(&app_json(json!([])))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+        assert!(app.platform.wait_for_change("slow").await);
+
+        // Lock the app so that its next change can't finish.
+        let mut lock = pool.begin().await.unwrap();
+        sqlx::query!("SELECT id FROM apps WHERE id = 'slow' FOR UPDATE")
+            .fetch_one(&mut *lock)
+            .await
+            .unwrap();
+
+        // One change runs and blocks, then unparsed changes pile up behind it without being
+        // coalesced, and then the channel fills.
+        let change_tx = app.platform.platform.change_tx.clone();
+        for _ in 0..(1 + MAX_WAITING + CHANGE_QUEUE_SIZE) {
+            change_tx
+                .send_async(AppFileInput {
+                    app_id: "slow".to_string(),
+                    contents: AppFileContents::Raw(app_json(json!([])).to_string()),
+                    merge_items: true,
+                    source: AppDataSource::File,
+                    owner: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let response = admin_user
+            .client
+            .put("apps/other")
+            .json(&app_json(json!([])))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        assert!(response.headers().contains_key("retry-after"));
+
+        lock.rollback().await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn update_requires_admin(pool: sqlx::PgPool) {
+        let (_app, BootstrappedData { user, .. }) = start_app(pool).await;
+
+        let app_data = json!({
+            "name": "Route App",
+            "path": "/bin/test",
+            "command": ["sh", "-c", "echo"],
+            "items": [],
+        });
+        let response = user
+            .client
+            .put("apps/route-app")
+            .json(&app_data)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+    }
+
+    #[sqlx::test]
+    async fn update_and_get_app(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { admin_user, .. }) = start_app(pool).await;
+
+        let response = admin_user
+            .client
+            .put("apps/route-app")
+            .json(&app_json(json!([
+                { "id": "a", "data": { "title": "A" }, "updated": "2024-01-01T00:00:00Z" }
+            ])))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+        assert!(app.platform.wait_for_change("route-app").await);
+
+        let info = app
+            .client
+            .get("apps/route-app")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(info["name"], "Route App");
+
+        let response = app
+            .client
+            .post("apps/route-app/items/a/dismiss")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let active = app
+            .client
+            .get("active_items")
+            .send()
+            .await
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        let undismissed = active[0]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|item| item["dismissed"] == false)
+            .count();
+        assert_eq!(undismissed, 0);
+    }
+
+    #[sqlx::test]
+    async fn item_feedback(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { admin_user, .. }) = start_app(pool).await;
+
+        let response = admin_user
+            .client
+            .put("apps/route-app")
+            .json(&app_json(json!([
+                { "id": "a", "data": { "title": "A" }, "updated": "2024-01-01T00:00:00Z" }
+            ])))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+        assert!(app.platform.wait_for_change("route-app").await);
+
+        // Dismissing twice only counts once.
+        for action in ["dismiss", "dismiss", "undismiss"] {
+            app.client
+                .post(&format!("apps/route-app/items/a/{action}"))
+                .send()
+                .await
+                .unwrap()
+                .error_for_status()
+                .unwrap();
+        }
+
+        let feedback = app
+            .client
+            .get("apps/route-app/feedback")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        let kinds = feedback
+            .iter()
+            .map(|f| f["kind"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["dismiss", "undismiss"]);
+        assert_eq!(feedback[0]["item_id"], "a");
+
+        let since_id = feedback[0]["id"].as_i64().unwrap();
+        let feedback = app
+            .client
+            .get("apps/route-app/feedback")
+            .query(&[("since_id", since_id)])
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(feedback.len(), 1);
+        assert_eq!(feedback[0]["kind"], "undismiss");
+    }
+
+    #[sqlx::test]
+    async fn reject_invalid_app_data(pool: sqlx::PgPool) {
+        let (_app, BootstrappedData { admin_user, .. }) = start_app(pool).await;
+
+        let response = admin_user
+            .client
+            .put("apps/route-app")
+            .json(&app_json(json!([
+                { "id": "a", "data": { "title": "A" }, "updated": "2024-01-01T00:00:00Z" },
+                { "id": "a", "data": { "title": "B" }, "updated": "2024-01-01T00:00:00Z" }
+            ])))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[sqlx::test]
+    async fn install_disable_uninstall(pool: sqlx::PgPool) {
+        let (
+            mut app,
+            BootstrappedData {
+                admin_user, user, ..
+            },
+        ) = start_app(pool).await;
+        let db = app.platform.platform.db.clone();
+
+        let install = json!({
+            "id": "installed",
+            "name": "Installed App",
+            "path": "/bin/installed",
+            "schedule": [{ "cron": "0 0 * * * *" }]
+        });
+
+        let response = user
+            .client
+            .post("apps")
+            .json(&install)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        let installed = admin_user
+            .client
+            .post("apps")
+            .json(&install)
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(installed["name"], "Installed App");
+        assert_eq!(installed["enabled"], true);
+
+        let response = admin_user
+            .client
+            .post("apps")
+            .json(&install)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 409);
+
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("installed:")
+            .await
+            .unwrap();
+        assert_eq!(jobs.len(), 1);
+
+        let schedules = app
+            .client
+            .get("apps/installed/schedules")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(
+            jobs[0],
+            format!("installed:{}", schedules[0]["id"].as_str().unwrap())
+        );
+
+        let response = app
+            .client
+            .get("apps/missing/schedules")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        // Publish an item so we can check that it is hidden while the app is disabled.
+        let response = admin_user
+            .client
+            .put("apps/installed")
+            .json(&json!({
+                "name": "Installed App",
+                "path": "/bin/installed",
+                "schedule": [{ "cron": "0 0 * * * *" }],
+                "items": [{ "id": "a", "data": { "title": "A" }, "updated": "2024-01-01T00:00:00Z" }]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 202);
+        assert!(app.platform.wait_for_change("installed").await);
+
+        let response = admin_user
+            .client
+            .post("apps/installed/disable")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("installed:")
+            .await
+            .unwrap();
+        assert!(jobs.is_empty());
+        let active = db.read_active_items(&AppVisibility::All).await.unwrap();
+        assert!(active.is_empty());
+        // The items are still there
+        assert_eq!(db.read_app_items("installed").await.unwrap().len(), 1);
+
+        let response = admin_user
+            .client
+            .post("apps/installed/enable")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("installed:")
+            .await
+            .unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            db.read_active_items(&AppVisibility::All)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let response = admin_user
+            .client
+            .delete("apps/installed")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(db.list_apps(&AppVisibility::All).await.unwrap().is_empty());
+        assert!(db.read_app_items("installed").await.unwrap().is_empty());
+
+        let response = admin_user
+            .client
+            .delete("apps/installed")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[sqlx::test]
+    async fn owned_apps_are_scoped(pool: sqlx::PgPool) {
+        let (
+            mut app,
+            BootstrappedData {
+                admin_user, user, ..
+            },
+        ) = start_app(pool).await;
+
+        let owners = [
+            ("public-app", None),
+            (
+                "org-app",
+                Some(AppOwner {
+                    organization_id: user.organization_id,
+                    user_id: None,
+                }),
+            ),
+            (
+                "admin-app",
+                Some(AppOwner {
+                    organization_id: admin_user.organization_id,
+                    user_id: Some(admin_user.user_id),
+                }),
+            ),
+        ];
+        for (app_id, owner) in owners {
+            let data = serde_json::from_value(app_json(json!([{
+                "id": "a",
+                "data": { "title": "A" },
+                "updated": "2024-01-01T00:00:00Z",
+            }])))
+            .unwrap();
+            app.platform
+                .platform
+                .change_tx
+                .send_async(AppFileInput {
+                    app_id: app_id.to_string(),
+                    contents: AppFileContents::Parsed(Box::new(data)),
+                    merge_items: false,
+                    source: AppDataSource::File,
+                    owner,
+                })
+                .await
+                .unwrap();
+            assert!(app.platform.wait_for_change(app_id).await);
+        }
+
+        async fn visible_apps(client: &filigree::testing::TestClient, path: &str) -> Vec<String> {
+            let response = client
+                .get(path)
+                .send()
+                .await
+                .unwrap()
+                .error_for_status()
+                .unwrap()
+                .json::<Vec<serde_json::Value>>()
+                .await
+                .unwrap();
+            let mut ids = response
+                .iter()
+                // Active items are grouped under each app.
+                .map(|entry| entry.get("app").unwrap_or(entry))
+                .map(|app| app["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        }
+
+        // Someone who isn't logged in only sees apps without an owner.
+        assert_eq!(visible_apps(&app.client, "apps").await, vec!["public-app"]);
+        assert_eq!(
+            visible_apps(&app.client, "active_items").await,
+            vec!["public-app"]
+        );
+
+        // Other users in the organization don't see apps owned by a single user.
+        assert_eq!(
+            visible_apps(&user.client, "apps").await,
+            vec!["org-app", "public-app"]
+        );
+        assert_eq!(
+            visible_apps(&admin_user.client, "active_items").await,
+            vec!["admin-app", "org-app", "public-app"]
+        );
+
+        let response = user.client.get("apps/admin-app").send().await.unwrap();
+        assert_eq!(response.status(), 404);
+        let response = user
+            .client
+            .post("apps/admin-app/items/a/dismiss")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        let response = app
+            .client
+            .post("apps/org-app/items/a/undismiss")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        // Writing items needs an admin who can see the app.
+        let item =
+            json!({ "id": "b", "data": { "title": "B" }, "updated": "2024-01-01T00:00:00Z" });
+        let response = user
+            .client
+            .post("apps/org-app/item")
+            .json(&item)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        admin_user
+            .client
+            .post("apps/admin-app/item")
+            .json(&item)
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+
+        let response = user
+            .client
+            .get("apps/admin-app/feedback")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        let response = admin_user
+            .client
+            .get("apps/admin-app")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[sqlx::test]
+    async fn concurrent_installs(pool: sqlx::PgPool) {
+        let (app, _) = start_app(pool).await;
+        let db = app.platform.platform.db.clone();
+
+        let install = |name: &str| AppInstallData {
+            id: "installed".to_string(),
+            name: name.to_string(),
+            path: "/bin/installed".to_string(),
+            command: None,
+            working_dir: None,
+            output: AppOutput::File,
+            schedule: Vec::new(),
+            builtin_source: None,
+        };
+        let (first, second) = tokio::join!(
+            db.install_app(&install("First")),
+            db.install_app(&install("Second"))
+        );
+
+        // Exactly one install wins, and the other doesn't overwrite it.
+        let (installed, failed) = match (first, second) {
+            (Ok(app), Err(e)) | (Err(e), Ok(app)) => (app, e),
+            (first, second) => panic!("expected one install to fail: {first:?} {second:?}"),
+        };
+        assert!(matches!(failed.current_context(), Error::AppAlreadyExists));
+        let stored = db.get_apps(&["installed".to_string()]).await.unwrap();
+        assert_eq!(stored[0].name, installed.name);
+    }
+
+    #[sqlx::test]
+    async fn get_missing_app(pool: sqlx::PgPool) {
+        let (app, _) = start_app(pool).await;
+
+        let response = app.client.get("apps/no-app").send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[sqlx::test]
+    async fn schedule_status(pool: sqlx::PgPool) {
+        let (app, BootstrappedData { admin_user, .. }) = start_app(pool).await;
+        let db = app.platform.platform.db.clone();
+
+        admin_user
+            .client
+            .post("apps")
+            .json(&json!({
+                "id": "local",
+                "name": "Local App",
+                "path": "/bin/local",
+                "schedule": [
+                    { "cron": "0 0 9 * * *", "timezone": "America/New_York" },
+                    { "cron": "0 0 * * * *" }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+
+        let status = app
+            .client
+            .get("apps/local")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(status["name"], "Local App");
+        let schedules = status["schedules"].as_array().unwrap();
+        assert_eq!(schedules.len(), 2);
+        assert_eq!(schedules[0]["timezone"], "America/New_York");
+        assert_eq!(schedules[0]["next_runs"].as_array().unwrap().len(), 5);
+        assert_eq!(schedules[0]["last_run_at"], serde_json::Value::Null);
+
+        let schedules = app
+            .client
+            .get("apps/local/schedules?runs=2")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(schedules[1]["next_runs"].as_array().unwrap().len(), 2);
+
+        // Only the UTC schedule gets a recurring job. The other is queued when it is due.
+        let jobs = db
+            .task_queue
+            .list_recurring_jobs_with_prefix("local:")
+            .await
+            .unwrap();
+        assert_eq!(
+            jobs,
+            vec![format!("local:{}", schedules[1]["id"].as_str().unwrap())]
+        );
+
+        sqlx::query!("UPDATE schedules SET next_run_at = now() - interval '1 minute'")
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        db.queue_due_schedules().await.unwrap();
+        let next_run =
+            sqlx::query_scalar!("SELECT next_run_at FROM schedules WHERE timezone IS NOT NULL")
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+                .unwrap();
+        assert!(next_run > chrono::Utc::now());
+    }
 }
@@ -13,10 +13,21 @@ use http::StatusCode;
 use serde::Deserialize;
 
 use super::ServerState;
-use crate::{error::Error, items::Item};
+use crate::{
+    auth::{has_any_permission, Authed},
+    db::AppVisibility,
+    error::Error,
+    items::Item,
+};
 
-async fn get_active_items(State(state): State<ServerState>) -> Result<impl IntoResponse, Error> {
-    let items = state.orm.read_active_items().await?;
+async fn get_active_items(
+    State(state): State<ServerState>,
+    auth: Option<Authed>,
+) -> Result<impl IntoResponse, Error> {
+    let items = state
+        .orm
+        .read_active_items(&AppVisibility::for_auth(auth.as_deref()))
+        .await?;
     Ok(Json(items))
 }
 
@@ -30,7 +41,9 @@ struct DismissItemPath {
 async fn dismiss_item(
     DismissItemPath { app_id, item_id }: DismissItemPath,
     State(state): State<ServerState>,
+    auth: Option<Authed>,
 ) -> Result<impl IntoResponse, Error> {
+    super::require_visible_app(&state, auth.as_deref(), &app_id).await?;
     state
         .orm
         .set_item_dismissed(&app_id, &item_id, true)
@@ -48,7 +61,9 @@ struct UndismissItemPath {
 async fn undismiss_item(
     UndismissItemPath { app_id, item_id }: UndismissItemPath,
     State(state): State<ServerState>,
+    auth: Option<Authed>,
 ) -> Result<impl IntoResponse, Error> {
+    super::require_visible_app(&state, auth.as_deref(), &app_id).await?;
     state
         .orm
         .set_item_dismissed(&app_id, &item_id, false)
@@ -64,10 +79,12 @@ struct PostItemQuery {
 
 async fn post_item(
     State(state): State<ServerState>,
+    auth: Authed,
     Path(app_id): Path<String>,
     Query(query): Query<PostItemQuery>,
     Json(data): Json<AppItem>,
 ) -> Result<impl IntoResponse, Error> {
+    super::require_visible_app(&state, Some(&*auth), &app_id).await?;
     let item = Item::from_app_item(app_id, data);
     state
         .orm
@@ -80,7 +97,11 @@ async fn post_item(
 pub fn routes() -> Router<ServerState> {
     Router::new()
         .route("/active_items", get(get_active_items))
-        .route("/apps/:app_id/item", post(post_item))
+        // Writing items is limited to admins, like publishing app data.
+        .route(
+            "/apps/:app_id/item",
+            post(post_item).route_layer(has_any_permission(vec!["org_admin"])),
+        )
         .typed_post(dismiss_item)
         .typed_post(undismiss_item)
 }
@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use http::StatusCode;
+
+use super::ServerState;
+use crate::{
+    auth::{has_any_permission, AuthInfo, Authed},
+    error::Error,
+    AppFileContents, AppFileInput,
+};
+
+/// Check that the sender of the request can see the app. Payloads can be rejected before the
+/// app exists, and those have no owner to check.
+async fn check_app_visible(
+    state: &ServerState,
+    auth: &AuthInfo,
+    app_id: &str,
+) -> Result<(), Error> {
+    if !state.orm.get_apps(&[app_id.to_string()]).await?.is_empty() {
+        super::require_visible_app(state, Some(auth), app_id).await?;
+    }
+    Ok(())
+}
+
+async fn list_rejected_payloads(
+    Path(app_id): Path<String>,
+    State(state): State<ServerState>,
+    auth: Authed,
+) -> Result<impl IntoResponse, Error> {
+    check_app_visible(&state, &auth, &app_id).await?;
+    let payloads = state.orm.get_rejected_payloads(&app_id).await?;
+    Ok(Json(payloads))
+}
+
+/// Send a rejected payload through the change pipeline again. The payload is removed from the
+/// store once it has been queued, and will be saved again if it is still rejected.
+async fn replay_rejected_payload(
+    Path((app_id, id)): Path<(String, i64)>,
+    State(state): State<ServerState>,
+    auth: Authed,
+) -> Result<impl IntoResponse, Error> {
+    check_app_visible(&state, &auth, &app_id).await?;
+    let payload = state
+        .orm
+        .get_rejected_payload(&app_id, id)
+        .await?
+        .ok_or(Error::NotFound("Rejected payload"))?;
+
+    let app_data = AppFileInput {
+        app_id: app_id.clone(),
+        contents: AppFileContents::Raw(payload.contents),
+        merge_items: payload.merge_items,
+        source: payload.source,
+        owner: None,
+    };
+
+    // Keep the payload if it couldn't be queued, so that it can be replayed later.
+    super::queue_change(&state, app_data).await?;
+    state.orm.remove_rejected_payload(&app_id, id).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Create the routes for inspecting rejected app data
+pub fn routes() -> Router<ServerState> {
+    Router::new()
+        // Rejected payloads can set the app's command, like publishing app data, so these are
+        // limited to admins.
+        .route(
+            "/apps/:app_id/rejected",
+            get(list_rejected_payloads).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route(
+            "/apps/:app_id/rejected/:id/replay",
+            post(replay_rejected_payload).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        tests::{start_app, BootstrappedData},
+        AppDataSource, AppFileContents, AppFileInput, AppOwner,
+    };
+
+    #[sqlx::test]
+    async fn rejected_payload_access(pool: sqlx::PgPool) {
+        let (
+            mut app,
+            BootstrappedData {
+                admin_user, user, ..
+            },
+        ) = start_app(pool).await;
+        let db = app.platform.platform.db.clone();
+
+        // An app that only `user` can see
+        let data = serde_json::from_value(json!({
+            "name": "Private",
+            "path": "/bin/private",
+            "items": [],
+        }))
+        .unwrap();
+        app.platform
+            .platform
+            .change_tx
+            .send_async(AppFileInput {
+                app_id: "private".to_string(),
+                contents: AppFileContents::Parsed(Box::new(data)),
+                merge_items: false,
+                source: AppDataSource::File,
+                owner: Some(AppOwner {
+                    organization_id: user.organization_id,
+                    user_id: Some(user.user_id),
+                }),
+            })
+            .await
+            .unwrap();
+        assert!(app.platform.wait_for_change("private").await);
+
+        for app_id in ["private", "new-app"] {
+            db.add_rejected_payload(
+                app_id,
+                AppDataSource::Http,
+                false,
+                "{",
+                "Invalid JSON",
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        let private_id = db.get_rejected_payloads("private").await.unwrap()[0].id;
+
+        let response = user
+            .client
+            .get("apps/new-app/rejected")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        let response = user
+            .client
+            .post(&format!("apps/private/rejected/{private_id}/replay"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        // Payloads for apps that don't exist yet have no owner to check.
+        let payloads = admin_user
+            .client
+            .get("apps/new-app/rejected")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap();
+        assert_eq!(payloads.len(), 1);
+
+        // Admins can't see apps owned by another user.
+        let response = admin_user
+            .client
+            .get("apps/private/rejected")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        let response = admin_user
+            .client
+            .post(&format!("apps/private/rejected/{private_id}/replay"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(db.get_rejected_payloads("private").await.unwrap().len(), 1);
+    }
+}
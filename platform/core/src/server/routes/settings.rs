@@ -0,0 +1,363 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{get, put},
+    Json, Router,
+};
+use error_stack::Report;
+
+use super::ServerState;
+use crate::{
+    app_settings::AppSettings,
+    auth::{has_any_permission, Authed},
+    error::Error,
+    models::user::UserId,
+    validation,
+};
+
+async fn read_settings(
+    state: &ServerState,
+    auth: &Authed,
+    app_id: &str,
+) -> Result<AppSettings, Error> {
+    super::require_visible_app(state, Some(&**auth), app_id).await?;
+    let settings = state
+        .orm
+        .get_app_settings(app_id, auth.organization_id, auth.user_id)
+        .await?
+        .ok_or(Error::NotFound("App"))?;
+    Ok(settings)
+}
+
+async fn get_settings(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(app_id): Path<String>,
+) -> Result<impl IntoResponse, Error> {
+    let settings = read_settings(&state, &auth, &app_id).await?;
+    Ok(Json(settings))
+}
+
+/// Validate new settings against the app's schema and save them.
+async fn write_settings(
+    state: &ServerState,
+    auth: &Authed,
+    app_id: &str,
+    user_id: Option<UserId>,
+    value: serde_json::Value,
+) -> Result<AppSettings, Error> {
+    let current = read_settings(state, auth, app_id).await?;
+    let Some(schema) = current.schema else {
+        return Err(Report::new(Error::InvalidSettings)
+            .attach_printable("This app does not have any settings")
+            .into());
+    };
+
+    let issues = validation::validate_settings(&schema, &value);
+    if !issues.is_empty() {
+        let report = issues
+            .into_iter()
+            .fold(Report::new(Error::InvalidSettings), |report, issue| {
+                report.attach_printable(issue.to_string())
+            });
+        return Err(report.into());
+    }
+
+    state
+        .orm
+        .set_app_settings(app_id, auth.organization_id, user_id, &value)
+        .await?;
+
+    read_settings(state, auth, app_id).await
+}
+
+async fn put_organization_settings(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(app_id): Path<String>,
+    Json(value): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, Error> {
+    let settings = write_settings(&state, &auth, &app_id, None, value).await?;
+    Ok(Json(settings))
+}
+
+async fn put_user_settings(
+    State(state): State<ServerState>,
+    auth: Authed,
+    Path(app_id): Path<String>,
+    Json(value): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, Error> {
+    let settings = write_settings(&state, &auth, &app_id, Some(auth.user_id), value).await?;
+    Ok(Json(settings))
+}
+
+/// Create the app settings routes
+pub fn routes() -> Router<ServerState> {
+    Router::new()
+        .route("/apps/:app_id/settings", get(get_settings))
+        .route(
+            "/apps/:app_id/settings/organization",
+            put(put_organization_settings).route_layer(has_any_permission(vec!["org_admin"])),
+        )
+        .route("/apps/:app_id/settings/user", put(put_user_settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use glance_app::APP_SETTINGS_SUBDIR;
+    use serde_json::json;
+
+    use crate::{
+        models::organization::OrganizationId,
+        tests::{platform::app_data, start_app, BootstrappedData},
+    };
+
+    #[sqlx::test]
+    async fn settings(pool: sqlx::PgPool) {
+        let (
+            mut app,
+            BootstrappedData {
+                admin_user, user, ..
+            },
+        ) = start_app(pool).await;
+
+        let data = app_data(json!({
+            "name": "Weather",
+            "path": "/bin/weather",
+            "settings_schema": {
+                "type": "object",
+                "properties": {
+                    "lat": { "type": "number" },
+                    "lon": { "type": "number" },
+                    "units": { "enum": ["metric", "imperial"] }
+                }
+            }
+        }));
+        assert!(app.platform.send_app_data("weather", data, false).await);
+
+        let response = admin_user
+            .client
+            .put("apps/weather/settings/organization")
+            .json(&json!({ "lat": 47.6, "lon": -122.3, "units": "metric" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = user
+            .client
+            .put("apps/weather/settings/organization")
+            .json(&json!({ "lat": 0 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        let response = user
+            .client
+            .put("apps/weather/settings/user")
+            .json(&json!({ "units": "kelvin" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        let settings = user
+            .client
+            .put("apps/weather/settings/user")
+            .json(&json!({ "units": "imperial" }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(
+            settings["values"],
+            json!({ "lat": 47.6, "lon": -122.3, "units": "imperial" })
+        );
+
+        // The admin sees only the organization values.
+        let settings = admin_user
+            .client
+            .get("apps/weather/settings")
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(
+            settings["values"],
+            json!({ "lat": 47.6, "lon": -122.3, "units": "metric" })
+        );
+        assert_eq!(settings["schema"]["type"], "object");
+
+        let file = app
+            .platform
+            .base_dir
+            .path()
+            .join(APP_SETTINGS_SUBDIR)
+            .join("weather.json");
+        let file_settings =
+            serde_json::from_slice::<serde_json::Value>(&std::fs::read(file).unwrap()).unwrap();
+        assert_eq!(
+            file_settings,
+            json!({ "lat": 47.6, "lon": -122.3, "units": "metric" })
+        );
+
+        let platform_settings = app
+            .platform
+            .platform
+            .db
+            .get_platform_app_settings("weather")
+            .await
+            .unwrap();
+        assert_eq!(platform_settings, Some(file_settings));
+    }
+
+    #[sqlx::test]
+    async fn app_without_settings(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { user, .. }) = start_app(pool).await;
+
+        let data = app_data(json!({ "name": "Plain", "path": "/bin/plain" }));
+        assert!(app.platform.send_app_data("plain", data, false).await);
+
+        let response = user
+            .client
+            .put("apps/plain/settings/user")
+            .json(&json!({ "a": 1 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        let response = user
+            .client
+            .get("apps/missing/settings")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[sqlx::test]
+    async fn platform_settings_by_organization(pool: sqlx::PgPool) {
+        let (mut app, BootstrappedData { organization, .. }) = start_app(pool.clone()).await;
+
+        let data = app_data(json!({ "name": "Weather", "path": "/bin/weather" }));
+        assert!(app.platform.send_app_data("weather", data, false).await);
+
+        let other_org = OrganizationId::new();
+        sqlx::query!(
+            "INSERT INTO organizations (id, name) VALUES ($1, 'Other')",
+            other_org.as_uuid()
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let db = &app.platform.platform.db;
+        db.set_app_settings(
+            "weather",
+            organization.id,
+            None,
+            &json!({ "units": "metric" }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_platform_app_settings("weather").await.unwrap(),
+            Some(json!({ "units": "metric" }))
+        );
+
+        // With settings from several organizations and no owner, none of them are used.
+        db.set_app_settings("weather", other_org, None, &json!({ "units": "imperial" }))
+            .await
+            .unwrap();
+        assert_eq!(db.get_platform_app_settings("weather").await.unwrap(), None);
+        let file = app
+            .platform
+            .base_dir
+            .path()
+            .join(APP_SETTINGS_SUBDIR)
+            .join("weather.json");
+        assert!(!file.exists());
+
+        sqlx::query!(
+            "UPDATE apps SET organization_id = $1 WHERE id = 'weather'",
+            other_org.as_uuid()
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            db.get_platform_app_settings("weather").await.unwrap(),
+            Some(json!({ "units": "imperial" }))
+        );
+    }
+
+    #[sqlx::test]
+    async fn settings_for_hidden_app(pool: sqlx::PgPool) {
+        let (
+            mut app,
+            BootstrappedData {
+                admin_user, user, ..
+            },
+        ) = start_app(pool.clone()).await;
+
+        let data = app_data(json!({
+            "name": "Weather",
+            "path": "/bin/weather",
+            "settings_schema": { "type": "object" }
+        }));
+        assert!(app.platform.send_app_data("weather", data, false).await);
+
+        // Give the app to another organization.
+        let other_org = OrganizationId::new();
+        sqlx::query!(
+            "INSERT INTO organizations (id, name) VALUES ($1, 'Other')",
+            other_org.as_uuid()
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "UPDATE apps SET organization_id = $1 WHERE id = 'weather'",
+            other_org.as_uuid()
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let response = user
+            .client
+            .get("apps/weather/settings")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        let response = user
+            .client
+            .put("apps/weather/settings/user")
+            .json(&json!({ "a": 1 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+
+        let response = admin_user
+            .client
+            .put("apps/weather/settings/organization")
+            .json(&json!({ "a": 1 }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+}
@@ -29,3 +29,43 @@ async fn route_not_found(pool: PgPool) {
         .expect("sending request");
     assert_eq!(response.status(), 404);
 }
+
+#[sqlx::test]
+async fn prometheus_metrics(pool: PgPool) {
+    let (app, _) = start_app(pool).await;
+    let client = &app.client;
+
+    let response = client.get("healthz").send().await.expect("getting health");
+    assert_eq!(response.status(), 200);
+
+    let response = reqwest::get(format!("{}/metrics", app.base_url))
+        .await
+        .expect("getting metrics");
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+    assert!(
+        body.contains(
+            r#"glance_http_requests_total{method="GET",route="/api/healthz",status="200"}"#
+        ),
+        "{body}"
+    );
+    assert!(body.contains("glance_db_pool_max_connections"), "{body}");
+}
+
+#[sqlx::test]
+async fn readiness(pool: PgPool) {
+    let (app, _) = start_app(pool).await;
+    let client = &app.client;
+
+    let response = client
+        .get("healthz/ready")
+        .send()
+        .await
+        .expect("getting readiness");
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "ok", "{body}");
+    assert_eq!(body["checks"]["database"]["status"], "ok");
+    assert_eq!(body["checks"]["task_queue"]["status"], "ok");
+    assert_eq!(body["checks"]["change_handler"]["status"], "ok");
+}
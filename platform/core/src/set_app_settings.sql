@@ -0,0 +1,17 @@
+INSERT INTO app_settings (
+  app_id,
+  organization_id,
+  user_id,
+  value)
+VALUES (
+  $1,
+  $2,
+  $3,
+  $4)
+ON CONFLICT (
+  app_id,
+  organization_id,
+  user_id)
+  DO UPDATE SET
+    value = EXCLUDED.value,
+    updated_at = NOW()
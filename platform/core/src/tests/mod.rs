@@ -1,3 +1,5 @@
+pub mod platform;
+
 use std::sync::{Arc, Mutex};
 
 use error_stack::Report;
@@ -9,8 +11,8 @@ use futures::future::FutureExt;
 use sqlx::{PgConnection, PgPool};
 use tracing::{event, instrument, Level};
 
+use self::platform::TestPlatform;
 use crate::{
-    db::DbInner,
     models::{
         organization::{Organization, OrganizationId},
         role::RoleId,
@@ -20,10 +22,20 @@ use crate::{
     Error,
 };
 
+/// Serve `router` on a local port, standing in for an external HTTP service. Returns the base URL
+/// of the server, which runs until the test ends.
+pub async fn serve_stub(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    format!("http://{addr}")
+}
+
 pub struct TestApp {
     /// Hold on to the shutdown signal so the server stays alive
     pub shutdown_tx: tokio::sync::oneshot::Sender<()>,
-    pub temp_data_dir: tempfile::TempDir,
+    /// The platform backing the server, with its temporary data directory
+    pub platform: TestPlatform,
     pub client: TestClient,
     pub base_url: String,
     pub pg_pool: PgPool,
@@ -80,21 +92,13 @@ pub async fn start_app_with_options(
     let email_service = filigree::email::services::test_service::TestEmailService::new();
     let sent_emails = email_service.emails.clone();
 
-    let temp_data_dir = tempfile::tempdir().unwrap();
-
     let listener = crate::server::create_tcp_listener("127.0.0.1", 0)
         .await
         .unwrap();
     let port = listener.local_addr().unwrap().port();
     let base_url = format!("http://127.0.0.1:{port}");
 
-    let db = Arc::new(
-        DbInner::new(pg_pool.clone(), temp_data_dir.path())
-            .await
-            .unwrap(),
-    );
-
-    let (change_tx, _) = flume::unbounded();
+    let platform = TestPlatform::new(pg_pool.clone()).await;
 
     let config = crate::server::Config {
         env: "test".into(),
@@ -105,8 +109,9 @@ pub async fn start_app_with_options(
         },
         insecure: true,
         request_timeout: std::time::Duration::from_secs(30),
-        change_tx,
-        db,
+        change_tx: platform.platform.change_tx.clone(),
+        health: platform.platform.health.clone(),
+        db: platform.platform.db.clone(),
         api_cors: filigree::auth::CorsSetting::default(),
         hosts: vec![],
         cookie_configuration: SessionCookieBuilder::new(
@@ -150,7 +155,7 @@ pub async fn start_app_with_options(
         server_task,
         sent_emails,
         pg_pool,
-        temp_data_dir,
+        platform,
     };
 
     (app, bootstrapped_data)
@@ -0,0 +1,127 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use glance_app::{AppData, APP_DATA_SUBDIR};
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+
+use crate::{
+    AppDataSource, AppFileContents, AppFileInput, ChangeProcessed, Platform, PlatformOptions,
+    WatchedRoot,
+};
+
+/// How long to wait for a change to be processed before failing the test
+const CHANGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A [Platform] running against a temporary data directory, with helpers to submit app data
+/// and wait for it to be reconciled.
+pub struct TestPlatform {
+    pub platform: Platform,
+    pub base_dir: tempfile::TempDir,
+    processed_rx: broadcast::Receiver<ChangeProcessed>,
+}
+
+impl TestPlatform {
+    pub async fn new(pg_pool: PgPool) -> Self {
+        Self::with_roots(pg_pool, |_| Vec::new()).await
+    }
+
+    /// Start a platform that watches the roots returned by `roots`, which is given the base
+    /// directory.
+    pub async fn with_roots(
+        pg_pool: PgPool,
+        roots: impl FnOnce(&Path) -> Vec<WatchedRoot>,
+    ) -> Self {
+        let base_dir = tempfile::tempdir().unwrap();
+        let platform = Platform::new(PlatformOptions {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            watched_roots: roots(base_dir.path()),
+            db: pg_pool,
+            enable_scheduled_tasks: false,
+            interpreters: Default::default(),
+            api_url: None,
+        })
+        .await
+        .expect("creating platform");
+
+        let processed_rx = platform.subscribe_changes();
+
+        Self {
+            platform,
+            base_dir,
+            processed_rx,
+        }
+    }
+
+    pub fn app_data_dir(&self) -> PathBuf {
+        self.base_dir.path().join(APP_DATA_SUBDIR)
+    }
+
+    /// Write an app's data file and wait for the platform to process it.
+    pub async fn write_app_file(&mut self, app_id: &str, data: &serde_json::Value) -> bool {
+        // Write and rename so that the watcher only sees the complete file.
+        let tmp_dir = self.base_dir.path().join("tmp");
+        std::fs::create_dir_all(&tmp_dir).unwrap();
+        let tmp_path = tmp_dir.join(format!("{app_id}.json"));
+        std::fs::write(&tmp_path, serde_json::to_vec(data).unwrap()).unwrap();
+        std::fs::rename(
+            &tmp_path,
+            self.app_data_dir().join(format!("{app_id}.json")),
+        )
+        .unwrap();
+
+        self.wait_for_change(app_id).await
+    }
+
+    /// Delete an app's data file and wait for the platform to process it.
+    pub async fn remove_app_file(&mut self, app_id: &str) -> bool {
+        std::fs::remove_file(self.app_data_dir().join(format!("{app_id}.json"))).unwrap();
+        self.wait_for_change(app_id).await
+    }
+
+    /// Send app data directly to the change handler, as the HTTP API does, and wait for the
+    /// platform to process it.
+    pub async fn send_app_data(&mut self, app_id: &str, data: AppData, merge_items: bool) -> bool {
+        self.platform
+            .change_tx
+            .send_async(AppFileInput {
+                app_id: app_id.to_string(),
+                contents: AppFileContents::Parsed(Box::new(data)),
+                merge_items,
+                source: AppDataSource::Http,
+                owner: None,
+            })
+            .await
+            .unwrap();
+
+        self.wait_for_change(app_id).await
+    }
+
+    /// Wait until the platform finishes processing a change for the given app, and return
+    /// whether it succeeded.
+    pub async fn wait_for_change(&mut self, app_id: &str) -> bool {
+        tokio::time::timeout(CHANGE_TIMEOUT, async {
+            loop {
+                match self.processed_rx.recv().await {
+                    Ok(processed) if processed.app_id == app_id => return processed.success,
+                    Ok(_) => {}
+                    // Notifications for other apps may have been dropped, but later ones will
+                    // still arrive.
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => {
+                        panic!("change handler stopped")
+                    }
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for change")
+    }
+}
+
+/// Build [AppData] for tests from a JSON value.
+pub fn app_data(value: serde_json::Value) -> AppData {
+    serde_json::from_value(value).expect("parsing test app data")
+}
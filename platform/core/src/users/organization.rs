@@ -23,6 +23,9 @@ const USER_DEFAULT_PERMISSIONS: &[&str] = &[
     "User::read",
     "User::write",
     "User::owner",
+    "Board::read",
+    "Board::write",
+    "Board::owner",
 ];
 
 pub struct CreatedOrganization {
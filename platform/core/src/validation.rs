@@ -0,0 +1,321 @@
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
+use error_stack::Report;
+use glance_app::AppData;
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The JSON Schema for [AppData], as generated by the `write_schema` dev utility.
+const APP_DATA_SCHEMA: &str = include_str!("../../../schema/app_data.json");
+
+fn app_data_schema() -> &'static JSONSchema {
+    static SCHEMA: OnceLock<JSONSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        let schema = serde_json::from_str::<serde_json::Value>(APP_DATA_SCHEMA)
+            .expect("parsing app data schema");
+        JSONSchema::compile(&schema).expect("compiling app data schema")
+    })
+}
+
+/// A single problem found while validating app data
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// A JSON pointer to the location of the problem in the submitted data. This is an empty
+    /// string if the problem applies to the document as a whole.
+    pub path: String,
+    /// A description of the problem
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub(crate) fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root): {}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// App data that failed validation, with every problem that was found.
+#[derive(Debug, Error)]
+pub struct AppDataValidationError {
+    /// The problems with the data
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl std::fmt::Display for AppDataValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "App data failed validation")?;
+        for issue in &self.issues {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse raw app data, checking it against the JSON Schema and the semantic rules in
+/// [validate_app_data].
+pub fn parse_app_data(contents: &str) -> Result<AppData, Report<AppDataValidationError>> {
+    let value = serde_json::from_str::<serde_json::Value>(contents).map_err(|e| {
+        Report::new(AppDataValidationError {
+            issues: vec![ValidationIssue::new("", e.to_string())],
+        })
+    })?;
+
+    if let Err(errors) = app_data_schema().validate(&value) {
+        let issues = errors
+            .map(|e| ValidationIssue::new(e.instance_path.to_string(), e.to_string()))
+            .collect();
+        return Err(Report::new(AppDataValidationError { issues }));
+    }
+
+    // The schema should catch nearly everything, but fall back to locating deserialization
+    // errors in case the two disagree.
+    let app = serde_path_to_error::deserialize::<_, AppData>(value).map_err(|e| {
+        let path = json_pointer(e.path());
+        Report::new(AppDataValidationError {
+            issues: vec![ValidationIssue::new(path, e.into_inner().to_string())],
+        })
+    })?;
+
+    validate_app_data(&app)?;
+    Ok(app)
+}
+
+/// Check the rules for app data that can not be expressed in the JSON Schema.
+pub fn validate_app_data(app: &AppData) -> Result<(), Report<AppDataValidationError>> {
+    let mut issues = Vec::new();
+
+    let mut seen_ids = HashMap::with_capacity(app.items.len());
+    for (i, item) in app.items.iter().enumerate() {
+        if let Some(first) = seen_ids.get(item.id.as_str()) {
+            issues.push(ValidationIssue::new(
+                format!("/items/{i}/id"),
+                format!(
+                    "Duplicate item id {:?}, first used at /items/{first}",
+                    item.id
+                ),
+            ));
+        } else {
+            seen_ids.insert(item.id.as_str(), i);
+        }
+
+        if let Some(url) = item.data.url.as_deref() {
+            if let Err(e) = url::Url::parse(url) {
+                issues.push(ValidationIssue::new(
+                    format!("/items/{i}/data/url"),
+                    format!("Invalid URL {url:?}: {e}"),
+                ));
+            }
+        }
+    }
+
+    if let Some(schema) = app.settings_schema.as_ref() {
+        let compiled = serde_json::to_value(schema)
+            .map_err(|e| e.to_string())
+            .and_then(|schema| {
+                JSONSchema::compile(&schema)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = compiled {
+            issues.push(ValidationIssue::new(
+                "/settings_schema",
+                format!("Invalid settings schema: {e}"),
+            ));
+        }
+    }
+
+    if app.command.as_ref().is_some_and(|c| c.is_empty()) {
+        issues.push(ValidationIssue::new(
+            "/command",
+            "The command must not be empty",
+        ));
+    }
+
+    for (i, schedule) in app.schedule.iter().enumerate() {
+        if let Err(e) = cron::Schedule::from_str(&schedule.cron) {
+            issues.push(ValidationIssue::new(
+                format!("/schedule/{i}/cron"),
+                format!("Invalid cron spec {:?}: {e}", schedule.cron),
+            ));
+        }
+
+        if schedule.command.as_ref().is_some_and(|c| c.is_empty()) {
+            issues.push(ValidationIssue::new(
+                format!("/schedule/{i}/command"),
+                "The command must not be empty",
+            ));
+        }
+
+        if let Some(timezone) = schedule.timezone.as_deref() {
+            if chrono_tz::Tz::from_str(timezone).is_err() {
+                issues.push(ValidationIssue::new(
+                    format!("/schedule/{i}/timezone"),
+                    format!("Unknown timezone {timezone:?}"),
+                ));
+            }
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(Report::new(AppDataValidationError { issues }))
+    }
+}
+
+/// Check an app's settings against the schema that the app declared, returning any problems.
+pub fn validate_settings(
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+) -> Vec<ValidationIssue> {
+    let schema = match JSONSchema::compile(schema) {
+        Ok(schema) => schema,
+        Err(e) => {
+            return vec![ValidationIssue::new(
+                "",
+                format!("The app's settings schema is invalid: {e}"),
+            )]
+        }
+    };
+
+    match schema.validate(value) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| ValidationIssue::new(e.instance_path.to_string(), e.to_string()))
+            .collect(),
+    }
+}
+
+/// Convert a deserialization path into a JSON pointer
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .filter_map(|segment| match segment {
+            serde_path_to_error::Segment::Seq { index } => Some(index.to_string()),
+            serde_path_to_error::Segment::Map { key } => {
+                Some(key.replace('~', "~0").replace('/', "~1"))
+            }
+            serde_path_to_error::Segment::Enum { variant } => Some(variant.clone()),
+            serde_path_to_error::Segment::Unknown => None,
+        })
+        .map(|segment| format!("/{segment}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_errors_are_located() {
+        let data = r##"{
+            "name": "Test",
+            "path": "/bin/test",
+            "items": [{ "id": "a", "data": {}, "updated": "2024-01-01T00:00:00Z" }]
+        }"##;
+
+        let err = parse_app_data(data).expect_err("validation should fail");
+        let issues = &err.current_context().issues;
+        assert!(
+            issues.iter().any(|issue| issue.path == "/items/0/data"),
+            "issues: {issues:?}"
+        );
+    }
+
+    #[test]
+    fn semantic_errors() {
+        let data = r##"{
+            "name": "Test",
+            "path": "/bin/test",
+            "items": [
+                { "id": "a", "data": { "title": "A", "url": "not a url" }, "updated": "2024-01-01T00:00:00Z" },
+                { "id": "a", "data": { "title": "B" }, "updated": "2024-01-01T00:00:00Z" }
+            ],
+            "schedule": [
+                { "cron": "every tuesday" },
+                { "cron": "0 0 9 * * *", "timezone": "Mars/Olympus" }
+            ]
+        }"##;
+
+        let err = parse_app_data(data).expect_err("validation should fail");
+        let mut paths = err
+            .current_context()
+            .issues
+            .iter()
+            .map(|issue| issue.path.as_str())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "/items/0/data/url",
+                "/items/1/id",
+                "/schedule/0/cron",
+                "/schedule/1/timezone"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_settings_schema() {
+        let data = r##"{
+            "name": "Test",
+            "path": "/bin/test",
+            "settings_schema": { "type": "not-a-type" }
+        }"##;
+
+        let err = parse_app_data(data).expect_err("validation should fail");
+        let issues = &err.current_context().issues;
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, "/settings_schema");
+    }
+
+    #[test]
+    fn settings_validation() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "lat": { "type": "number" },
+                "lon": { "type": "number" }
+            },
+            "required": ["lat", "lon"]
+        });
+
+        let issues = validate_settings(&schema, &serde_json::json!({ "lat": 1.5, "lon": 2 }));
+        assert_eq!(issues, vec![]);
+
+        let issues = validate_settings(&schema, &serde_json::json!({ "lat": "north" }));
+        let mut paths = issues
+            .iter()
+            .map(|issue| issue.path.as_str())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(paths, vec!["", "/lat"]);
+    }
+
+    #[test]
+    fn valid_data() {
+        let data = r##"{
+            "name": "Test",
+            "path": "/bin/test",
+            "items": [
+                { "id": "a", "data": { "title": "A", "url": "https://example.com" }, "updated": "2024-01-01T00:00:00Z" }
+            ],
+            "schedule": [{ "cron": "0 */15 * * * *" }]
+        }"##;
+
+        parse_app_data(data).expect("validation should succeed");
+    }
+}
@@ -0,0 +1,52 @@
+WITH input AS (
+  SELECT
+    *
+  FROM
+    UNNEST($2::uuid[], $3::text[], $4::jsonb[], $5::int[], $6::text[], $7::timestamptz[],
+      $8::jsonb[], $9::text[]) WITH ORDINALITY AS input (id, cron, arguments, timeout, timezone,
+      next_run_at, command, working_dir, position)
+),
+upserted AS (
+INSERT INTO schedules (id, app_id, cron, arguments, timeout, timezone, next_run_at, command,
+  working_dir, position)
+  SELECT
+    id,
+    $1,
+    cron,
+    arguments,
+    timeout,
+    timezone,
+    next_run_at,
+    command,
+    working_dir,
+    (position - 1)::int
+  FROM
+    input
+  ON CONFLICT (app_id,
+    cron,
+    arguments)
+    DO UPDATE SET
+      timeout = EXCLUDED.timeout,
+      timezone = EXCLUDED.timezone,
+      -- Keep a pending run unless the timezone changed, so that a run which is due but not yet
+      -- queued isn't skipped.
+      next_run_at = CASE WHEN schedules.timezone IS NOT DISTINCT FROM EXCLUDED.timezone
+        AND schedules.next_run_at IS NOT NULL THEN
+        schedules.next_run_at
+      ELSE
+        EXCLUDED.next_run_at
+      END,
+      command = EXCLUDED.command,
+      working_dir = EXCLUDED.working_dir,
+      position = EXCLUDED.position,
+      updated_at = now()
+    RETURNING
+      id)
+  -- Existing schedules keep their IDs, so the new IDs only apply to new schedules.
+  DELETE FROM schedules
+  WHERE app_id = $1
+    AND id NOT IN (
+      SELECT
+        id
+      FROM
+        upserted)